@@ -0,0 +1,41 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use eq_gen::{Equation, NumberType, OperationConfig, OperationType, QuestionSet};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn config() -> OperationConfig {
+    OperationConfig {
+        answer_min: 1,
+        answer_max: 100,
+        value_min: 0,
+        value_max: 100,
+        allowed_numerics: [NumberType::Whole, NumberType::Negative]
+            .into_iter()
+            .collect(),
+        allowed_operations: [
+            (OperationType::Add, 4),
+            (OperationType::Subtract, 3),
+            (OperationType::Multiply, 2),
+            (OperationType::Divide, 1),
+        ]
+        .into(),
+    }
+}
+
+fn generate(c: &mut Criterion) {
+    let config = config();
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    for op_count in [1, 3] {
+        c.bench_function(&format!("rnd_compound {op_count} ops"), |b| {
+            b.iter(|| Equation::rnd_compound(&config, black_box(op_count), &mut rng))
+        });
+    }
+    c.bench_function("question set of 30", |b| {
+        b.iter(|| QuestionSet::generate(config.clone(), 3, 30, black_box(42)))
+    });
+}
+
+criterion_group!(benches, generate);
+criterion_main!(benches);
@@ -0,0 +1,230 @@
+//! Generates equations from the command line without starting the game, eg.
+//! `cargo run -p eq_gen --bin eqgen -- --ops add:3,multiply --count 20 --format csv`
+
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use eq_gen::{
+    NumberType, OperationConfig, OperationType, Parentheses, QuestionSet, Shape, Versioned,
+    Worksheet,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl From<Op> for OperationType {
+    fn from(op: Op) -> Self {
+        match op {
+            Op::Add => Self::Add,
+            Op::Subtract => Self::Subtract,
+            Op::Multiply => Self::Multiply,
+            Op::Divide => Self::Divide,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Numeric {
+    Whole,
+    Negative,
+}
+
+impl From<Numeric> for NumberType {
+    fn from(numeric: Numeric) -> Self {
+        match numeric {
+            Numeric::Whole => Self::Whole,
+            Numeric::Negative => Self::Negative,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Parens {
+    All,
+    // only where precedence needs them
+    Needed,
+}
+
+impl From<Parens> for Parentheses {
+    fn from(parens: Parens) -> Self {
+        match parens {
+            Parens::All => Self::All,
+            Parens::Needed => Self::Needed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    // one equation per line with its answer
+    Text,
+    // the whole question set, versioned
+    Json,
+    Csv,
+    // a printable worksheet
+    Html,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Generate calculoot equations without starting the game")]
+struct Args {
+    /// Smallest answer
+    #[arg(long, default_value_t = 1, allow_negative_numbers = true)]
+    answer_min: i64,
+    /// Largest answer
+    #[arg(long, default_value_t = 20, allow_negative_numbers = true)]
+    answer_max: i64,
+    /// Smallest value used to reach the answer
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    value_min: i64,
+    /// Largest value used to reach the answer
+    #[arg(long, default_value_t = 20, allow_negative_numbers = true)]
+    value_max: i64,
+    /// Operations to use, each optionally weighted as `name:weight`, eg. `add:6,subtract:3`
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = weighted::<Op>,
+        default_value = "add,subtract,multiply,divide"
+    )]
+    ops: Vec<(Op, u32)>,
+    /// Kinds of numbers to use, weighted the same way as operations
+    #[arg(long, value_delimiter = ',', value_parser = weighted::<Numeric>, default_value = "whole")]
+    numerics: Vec<(Numeric, u32)>,
+    /// Most operations in one equation, or exactly this many with `--shape`
+    #[arg(long, default_value_t = 1)]
+    op_count: u8,
+    /// How operations nest: `left-chain`, `right-chain`, `balanced`, `random` or a template
+    /// such as `(_ op _) × _`
+    #[arg(long)]
+    shape: Option<String>,
+    /// How many equations to generate
+    #[arg(long, default_value_t = 10)]
+    count: usize,
+    /// Seed for the generator, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Which parentheses equations are written with
+    #[arg(long, value_enum, default_value_t = Parens::All)]
+    parentheses: Parens,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Fill in the answers of an `html` worksheet
+    #[arg(long)]
+    answer_key: bool,
+}
+
+impl Args {
+    fn shape(&self) -> Option<Result<Shape, String>> {
+        let shape = match self.shape.as_deref()? {
+            "random" => Shape::Random(self.op_count),
+            "left-chain" => Shape::LeftChain(self.op_count),
+            "right-chain" => Shape::RightChain(self.op_count),
+            "balanced" => Shape::Balanced(self.op_count),
+            template => return Some(template.parse().map(Shape::Template)),
+        };
+        Some(Ok(shape))
+    }
+
+    fn config(&self) -> OperationConfig<i64> {
+        OperationConfig {
+            answer_min: self.answer_min,
+            answer_max: self.answer_max,
+            value_min: self.value_min,
+            value_max: self.value_max,
+            allowed_numerics: self.numerics.iter().map(|&(n, w)| (n.into(), w)).collect(),
+            allowed_operations: self.ops.iter().map(|&(op, w)| (op.into(), w)).collect(),
+        }
+    }
+}
+
+// a choice with an optional weight, 1 when left out
+fn weighted<T: ValueEnum>(arg: &str) -> Result<(T, u32), String> {
+    let (name, weight) = match arg.split_once(':') {
+        Some((name, weight)) => {
+            let weight = weight
+                .parse()
+                .map_err(|_| format!("`{weight}` is not a weight"))?;
+            (name, weight)
+        }
+        None => (arg, 1),
+    };
+    Ok((T::from_str(name, true)?, weight))
+}
+
+fn csv(set: &QuestionSet<i64>, parentheses: Parentheses) -> String {
+    let mut out = "equation,answer,difficulty,operations\n".to_string();
+    for eq in &set.equations {
+        let ops = eq
+            .operations()
+            .iter()
+            .map(|op| format!("{op:?}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        out.push_str(&format!(
+            "\"{}\",{},{},{ops}\n",
+            eq.written(parentheses),
+            eq.answer,
+            eq.difficulty()
+        ));
+    }
+    out
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.answer_min > args.answer_max || args.value_min > args.value_max {
+        eprintln!("ranges must have their minimum below their maximum");
+        return ExitCode::FAILURE;
+    }
+
+    if args.config().allowed_operations.is_empty() {
+        eprintln!("at least one operation needs a weight above zero");
+        return ExitCode::FAILURE;
+    }
+
+    let seed = args.seed.unwrap_or_else(|| rand::random_range(0..u64::MAX));
+    let set = match args.shape() {
+        None => QuestionSet::generate(args.config(), args.op_count, args.count, seed),
+        Some(Ok(shape)) => QuestionSet::generate_shaped(args.config(), &shape, args.count, seed),
+        Some(Err(e)) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if set.equations.len() < args.count {
+        eprintln!(
+            "only generated {} of {} equations, try wider ranges",
+            set.equations.len(),
+            args.count
+        );
+    }
+
+    let parentheses = args.parentheses.into();
+    match args.format {
+        Format::Text => {
+            for eq in &set.equations {
+                println!("{} = {}", eq.written(parentheses), eq.answer);
+            }
+        }
+        Format::Json => match Versioned::new(set).to_json() {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("could not write json: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Format::Csv => print!("{}", csv(&set, parentheses)),
+        Format::Html => {
+            let mut worksheet = Worksheet::from_set(set);
+            worksheet.parentheses = parentheses;
+            print!("{}", worksheet.to_html(args.answer_key));
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,337 @@
+use std::cmp;
+use std::fmt::{Debug, Display};
+
+use primes::is_prime;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::number::Number;
+use crate::operation::{OperationConfig, OperationType};
+use crate::shape::{Shape, Template};
+use crate::values::Value;
+
+// tries at each level of a shape before starting over from a new answer
+const SHAPE_ATTEMPTS: usize = 4;
+
+/// A binary operation on two values, either of which may be another equation, and the
+/// answer it works out to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Equation<N = i16> {
+    pub left: Box<Value<N>>,
+    pub right: Box<Value<N>>,
+    pub op: OperationType,
+    pub answer: N,
+}
+
+impl<N: Number> Equation<N> {
+    /// An equation from its parts, trusting `answer` to be correct
+    pub fn new(left: Value<N>, op: OperationType, right: Value<N>, answer: N) -> Self {
+        Self {
+            left: Box::new(left),
+            right: Box::new(right),
+            op,
+            answer,
+        }
+    }
+
+    /// Generates an equation with exactly `op_count` operations nested any way, ie. (a+b)+(c+d)
+    /// or a×(b-c)+d. `None` if no equation of that size fits the config.
+    pub fn rnd_compound(
+        op_config: &OperationConfig<N>,
+        op_count: u8,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        Self::rnd_shaped(op_config, &Shape::Random(op_count), rng).ok()
+    }
+
+    /// Generates an equation laid out exactly as `shape`, with an error saying why if it can't
+    pub fn rnd_shaped(
+        op_config: &OperationConfig<N>,
+        shape: &Shape,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        for _ in 0..SHAPE_ATTEMPTS {
+            let template = shape.template(rng);
+            if template.op_count() == 0 {
+                return Err("an equation needs at least one operation".to_string());
+            }
+            let answer = N::random_between(&op_config.answer_min, &op_config.answer_max, rng)
+                .ok_or("the answer range is empty")?;
+            if let Some(eq) = Self::rnd_template(answer, &template, op_config, rng) {
+                return Ok(eq);
+            }
+        }
+        Err(format!(
+            "no equation of {shape} fits the configured ranges and operations"
+        ))
+    }
+
+    // fills in a template from the outside in, each operation's operands becoming the answers
+    // of the operations nested under it
+    fn rnd_template(
+        answer: N,
+        template: &Template,
+        op_config: &OperationConfig<N>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        let Template::Operation { op, left, right } = template else {
+            return None;
+        };
+        'attempts: for _ in 0..SHAPE_ATTEMPTS {
+            let op = match op {
+                Some(op) => Some(*op),
+                None => Self::rnd_op(&answer, op_config, rng),
+            };
+            let Some(mut eq) = op.and_then(|op| Self::rnd_with(answer.clone(), op, op_config, rng))
+            else {
+                continue;
+            };
+            for (value, template) in [(&mut eq.left, left), (&mut eq.right, right)] {
+                if matches!(template.as_ref(), Template::Operation { .. }) {
+                    match Self::rnd_template(value.to_number(), template, op_config, rng) {
+                        Some(nested) => **value = Value::Equation(nested),
+                        None => continue 'attempts,
+                    }
+                }
+            }
+            return Some(eq);
+        }
+        None
+    }
+
+    /// Generates an equation with a single operation
+    pub fn rnd_single(
+        answer: N,
+        op_config: &OperationConfig<N>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        let mut attempts = 0;
+        let mut eq: Option<Self> = None;
+
+        while attempts < 20 && eq.is_none() {
+            eq = Self::rnd_op(&answer, op_config, rng)
+                .and_then(|op| Self::rnd_with(answer.clone(), op, op_config, rng));
+            attempts += 1;
+        }
+
+        eq
+    }
+
+    // an allowed operation that can reach the answer
+    fn rnd_op(
+        answer: &N,
+        op_config: &OperationConfig<N>,
+        rng: &mut impl Rng,
+    ) -> Option<OperationType> {
+        let ops = &op_config.allowed_operations;
+        if *answer == N::zero() {
+            ops.choose(rng, |op| {
+                *op != OperationType::Multiply && *op != OperationType::Divide
+            })
+        } else if answer.to_u64().is_some_and(is_prime) {
+            ops.choose(rng, |op| *op != OperationType::Multiply)
+        } else {
+            ops.choose(rng, |_| true)
+        }
+    }
+
+    fn rnd_with(
+        answer: N,
+        op: OperationType,
+        op_config: &OperationConfig<N>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        // each operation works backwards from the answer, skipping any pick that overflows
+        match op {
+            OperationType::Add => {
+                let mut attempts = 10;
+                while attempts > 0 {
+                    let left = op_config.rnd_number(rng);
+                    if let Some(right) = answer.checked_sub(&left)
+                        && valid_range(&left, &right, op_config)
+                    {
+                        return Some(Equation::new(
+                            left.into(),
+                            OperationType::Add,
+                            right.into(),
+                            answer,
+                        ));
+                    }
+                    attempts -= 1;
+                }
+                None
+            }
+            OperationType::Subtract => {
+                let mut attempts = 10;
+                while attempts > 0 {
+                    let left = op_config.rnd_number(rng);
+                    if let Some(right) = left.checked_sub(&answer)
+                        && valid_range(&left, &right, op_config)
+                    {
+                        return Some(Equation::new(
+                            left.into(),
+                            OperationType::Subtract,
+                            right.into(),
+                            answer,
+                        ));
+                    }
+                    attempts -= 1;
+                }
+                None
+            }
+            OperationType::Multiply => {
+                let mut attempts = 10;
+                while attempts > 0 {
+                    let left = find_divisible(&answer, op_config, rng);
+                    if let Some(right) = answer.checked_div(&left)
+                        && right != N::zero()
+                        && valid_range(&left, &right, op_config)
+                    {
+                        return Some(Equation::new(
+                            left.into(),
+                            OperationType::Multiply,
+                            right.into(),
+                            answer,
+                        ));
+                    }
+                    attempts -= 1;
+                }
+                None
+            }
+            OperationType::Divide => {
+                let mut attempts = 10;
+                while attempts > 0 {
+                    let right = op_config.rnd_number(rng);
+                    if let Some(left) = answer.checked_mul(&right)
+                        && left != N::zero()
+                        && right != N::zero()
+                        && valid_range(&right, &left, op_config)
+                    {
+                        return Some(Equation::new(
+                            left.into(),
+                            OperationType::Divide,
+                            right.into(),
+                            answer,
+                        ));
+                    }
+                    attempts -= 1;
+                }
+                None
+            }
+        }
+    }
+
+    /// The same equation with the operands of every addition and multiplication in a fixed
+    /// order, so `4 + 3` and `3 + 4` count as one fact
+    pub fn canonical(&self) -> Self {
+        let canonical = |value: &Value<N>| match value {
+            Value::Number(n) => Value::Number(n.clone()),
+            Value::Equation(e) => Value::Equation(e.canonical()),
+        };
+        let mut left = canonical(&self.left);
+        let mut right = canonical(&self.right);
+        let commutative = matches!(self.op, OperationType::Add | OperationType::Multiply);
+        if commutative && operand_key(&right) < operand_key(&left) {
+            std::mem::swap(&mut left, &mut right);
+        }
+        Self::new(left, self.op, right, self.answer.clone())
+    }
+
+    /// Every operation in the equation, outermost first
+    pub fn operations(&self) -> Vec<OperationType> {
+        let mut ops = vec![self.op];
+        for value in [self.left.as_ref(), self.right.as_ref()] {
+            if let Value::Equation(e) = value {
+                ops.extend(e.operations());
+            }
+        }
+        ops
+    }
+
+    /// A rough score of how hard the equation is, growing with the size of its numbers and
+    /// the operations used
+    pub fn difficulty(&self) -> u16 {
+        let left = match self.left.as_ref() {
+            Value::Number(_) => self.left.difficulty(),
+            Value::Equation(e) => e.difficulty(),
+        };
+        let right = match self.right.as_ref() {
+            Value::Number(_) => self.right.difficulty(),
+            Value::Equation(e) => e.difficulty(),
+        };
+        let op = match self.op {
+            OperationType::Add => 1,
+            OperationType::Subtract => 2,
+            OperationType::Multiply => 4,
+            OperationType::Divide => 4,
+        };
+
+        (left + right) * op
+    }
+}
+
+impl<N: Number> Display for Equation<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", fmt_rec(self, 0))
+    }
+}
+
+impl<N: Number> PartialOrd for Equation<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: Number> Ord for Equation<N> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.difficulty().cmp(&other.difficulty())
+    }
+}
+
+// numbers first, smallest first, then nested equations by how they're written
+fn operand_key<N: Number>(value: &Value<N>) -> (bool, N, String) {
+    match value {
+        Value::Number(n) => (false, n.clone(), String::new()),
+        Value::Equation(e) => (true, e.answer.clone(), e.to_string()),
+    }
+}
+
+fn fmt_rec<N: Number>(eq: &Equation<N>, d: u8) -> String {
+    let starting_parens = (0..=d).fold("".to_string(), |acc, _| format!("({acc}"));
+    let ending_parens = (0..=d).fold("".to_string(), |acc, _| format!("{acc})"));
+    let left = match eq.left.as_ref() {
+        Value::Number(n) => format!("{n}"),
+        Value::Equation(left) => {
+            format!("{starting_parens}{}{ending_parens}", fmt_rec(left, d + 1))
+        }
+    };
+    let right = match eq.right.as_ref() {
+        Value::Number(n) => format!("{n}"),
+        Value::Equation(right) => {
+            format!("{starting_parens}{}{ending_parens}", fmt_rec(right, d + 1))
+        }
+    };
+
+    format!("{left} {} {right}", eq.op)
+}
+
+fn valid_range<N: Number>(left: &N, right: &N, op_config: &OperationConfig<N>) -> bool {
+    *left >= op_config.value_min
+        && *left <= op_config.value_max
+        && *right >= op_config.value_min
+        && *right <= op_config.value_max
+}
+
+fn find_divisible<N: Number>(start: &N, op_config: &OperationConfig<N>, rng: &mut impl Rng) -> N {
+    let mut attempts = 0;
+    let mut left = op_config.rnd_number(rng);
+    while start.checked_div(&left).is_none() {
+        if attempts == 10 {
+            left = N::one()
+        } else {
+            left = op_config.rnd_number(rng);
+            attempts += 1;
+        }
+    }
+    left
+}
@@ -0,0 +1,181 @@
+use crate::precedence::needs_parentheses;
+use crate::{Equation, Number, OperationType, Parentheses, Value};
+
+/// One operation worked out while solving an equation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<N = i16> {
+    /// The operation worked out, both its sides plain numbers
+    pub worked: Equation<N>,
+    /// What's left of the whole equation afterwards, just the answer after the last step
+    pub remaining: Value<N>,
+}
+
+impl<N: Number> Equation<N> {
+    /// Solves the equation one operation at a time the way it would be done on paper:
+    /// parenthesized operations first, then multiplication and division before addition and
+    /// subtraction, each from the left. The last step leaves only the answer.
+    pub fn steps(&self, parentheses: Parentheses) -> Vec<Step<N>> {
+        let mut remaining = match parentheses {
+            Parentheses::All => Value::Equation(self.clone()),
+            // worked as it reads, which can group differently to how it was built
+            Parentheses::Needed => regroup(self).unwrap_or_else(|| Value::Equation(self.clone())),
+        };
+        let mut steps = Vec::new();
+        while let Value::Equation(eq) = &remaining {
+            let path = next_step(eq, parentheses);
+            let worked = reduce(&mut remaining, &path);
+            steps.push(Step {
+                worked,
+                remaining: remaining.clone(),
+            });
+        }
+        steps
+    }
+}
+
+impl<N: Number> Value<N> {
+    /// The number, or the nested equation written out with the given parentheses
+    pub fn written(&self, parentheses: Parentheses) -> String {
+        match self {
+            Self::Number(n) => n.to_string(),
+            Self::Equation(eq) => eq.written(parentheses),
+        }
+    }
+}
+
+// the equation grouped the way it reads with only the needed parentheses, multiplication and
+// division binding first and each run of operations going from the left, ie. 2 + (5 - 3) is
+// written 2 + 5 - 3 and read as (2 + 5) - 3. `None` if an answer overflows on the way.
+fn regroup<N: Number>(eq: &Equation<N>) -> Option<Value<N>> {
+    let mut operands = Vec::new();
+    let mut ops = Vec::new();
+    flatten(eq, &mut operands, &mut ops)?;
+
+    let mut operands = operands.into_iter();
+    let mut terms = vec![operands.next()?];
+    let mut additive = Vec::new();
+    for (op, operand) in ops.into_iter().zip(operands) {
+        if op.precedence() > 1 {
+            let left = terms.pop()?;
+            terms.push(join(left, op, operand)?);
+        } else {
+            additive.push(op);
+            terms.push(operand);
+        }
+    }
+    let mut terms = terms.into_iter();
+    let first = terms.next()?;
+    additive
+        .into_iter()
+        .zip(terms)
+        .try_fold(first, |left, (op, right)| join(left, op, right))
+}
+
+// the operands and operations as they read from left to right, each parenthesized group
+// regrouped on its own
+fn flatten<N: Number>(
+    eq: &Equation<N>,
+    operands: &mut Vec<Value<N>>,
+    ops: &mut Vec<OperationType>,
+) -> Option<()> {
+    for (value, right) in [(eq.left.as_ref(), false), (eq.right.as_ref(), true)] {
+        if right {
+            ops.push(eq.op);
+        }
+        match value {
+            Value::Number(_) => operands.push(value.clone()),
+            Value::Equation(nested) if needs_parentheses(value, eq.op, right) => {
+                operands.push(regroup(nested)?);
+            }
+            Value::Equation(nested) => flatten(nested, operands, ops)?,
+        }
+    }
+    Some(())
+}
+
+fn join<N: Number>(left: Value<N>, op: OperationType, right: Value<N>) -> Option<Value<N>> {
+    let answer = op.apply(&left.to_number(), &right.to_number())?;
+    Some(Value::Equation(Equation::new(left, op, right, answer)))
+}
+
+// an operation whose sides are both numbers, found by going left (false) or right (true) from
+// the top of the equation
+struct Candidate {
+    path: Vec<bool>,
+    parenthesized: bool,
+    precedence: u8,
+}
+
+// the path to the operation to work out next
+fn next_step<N: Number>(eq: &Equation<N>, parentheses: Parentheses) -> Vec<bool> {
+    let mut candidates = Vec::new();
+    find_candidates(eq, false, &mut Vec::new(), parentheses, &mut candidates);
+    // candidates are found left to right, so the first of the best is the leftmost
+    let best = candidates
+        .iter()
+        .map(|c| (c.parenthesized, c.precedence))
+        .max()
+        .unwrap_or_default();
+    candidates
+        .into_iter()
+        .find(|c| (c.parenthesized, c.precedence) == best)
+        .map(|c| c.path)
+        .unwrap_or_default()
+}
+
+fn find_candidates<N: Number>(
+    eq: &Equation<N>,
+    parenthesized: bool,
+    path: &mut Vec<bool>,
+    parentheses: Parentheses,
+    candidates: &mut Vec<Candidate>,
+) {
+    let mut ready = true;
+    for (value, right) in [(eq.left.as_ref(), false), (eq.right.as_ref(), true)] {
+        let Value::Equation(nested) = value else {
+            continue;
+        };
+        ready = false;
+        let nested_parenthesized = match parentheses {
+            Parentheses::All => true,
+            Parentheses::Needed => needs_parentheses(value, eq.op, right),
+        };
+        path.push(right);
+        find_candidates(
+            nested,
+            parenthesized || nested_parenthesized,
+            path,
+            parentheses,
+            candidates,
+        );
+        path.pop();
+    }
+    if ready {
+        // with parentheses everywhere precedence never decides the order
+        let precedence = match parentheses {
+            Parentheses::All => 0,
+            Parentheses::Needed => eq.op.precedence(),
+        };
+        candidates.push(Candidate {
+            path: path.clone(),
+            parenthesized,
+            precedence,
+        });
+    }
+}
+
+// replaces the operation at the end of the path with its answer, returning the operation
+fn reduce<N: Number>(value: &mut Value<N>, path: &[bool]) -> Equation<N> {
+    let Value::Equation(eq) = value else {
+        unreachable!("a step's path always leads to an operation");
+    };
+    match path.split_first() {
+        Some((false, rest)) => reduce(&mut eq.left, rest),
+        Some((true, rest)) => reduce(&mut eq.right, rest),
+        None => {
+            let worked = eq.clone();
+            *value = Value::Number(worked.answer.clone());
+            worked
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Random arithmetic equations for practising maths, as used by calculoot.
+//!
+//! An [`OperationConfig`] describes the equations wanted: the range of the answer, the range
+//! of the numbers used to reach it, and which operations and kinds of numbers are allowed.
+//! [`Equation::rnd_compound`] makes one equation from it and [`QuestionSet::generate`] makes a
+//! whole set from a seed, so the same questions can be made again.
+//!
+//! ```
+//! use eq_gen::{NumberType, OperationConfig, OperationType, QuestionSet};
+//!
+//! let config: OperationConfig = OperationConfig {
+//!     answer_min: 1,
+//!     answer_max: 20,
+//!     value_min: 0,
+//!     value_max: 20,
+//!     allowed_numerics: [NumberType::Whole].into_iter().collect(),
+//!     // twice as much adding as subtracting
+//!     allowed_operations: [(OperationType::Add, 2), (OperationType::Subtract, 1)].into(),
+//! };
+//! let set = QuestionSet::generate(config, 2, 10, 42);
+//! for eq in &set.equations {
+//!     println!("{eq} = {}", eq.answer);
+//! }
+//! ```
+//!
+//! Numbers are `i16` unless another [`Number`] is asked for, eg. `OperationConfig<i64>` for
+//! 4-digit addition or big multiplication. The generator uses checked arithmetic throughout
+//! and skips any candidate that would overflow.
+//!
+//! Nested equations are written with parentheses around each of them. For order of
+//! operations practice, [`Equation::written`] can leave out the ones precedence makes
+//! redundant, and [`Equation::rnd_precedence_trap`] makes equations that working left to
+//! right gets wrong. [`Equation::steps`] works any equation out one operation at a time to
+//! show how it's solved.
+//!
+//! The model serializes with serde. Standalone documents are wrapped in [`Versioned`] so
+//! they can be checked against [`SCHEMA_VERSION`] when read back.
+//!
+//! A [`Worksheet`] lays a set out as a printable HTML page, with or without its answer key.
+
+mod equation;
+mod explain;
+mod number;
+mod operation;
+mod precedence;
+mod question_set;
+mod schema;
+mod shape;
+mod values;
+mod weights;
+mod worksheet;
+
+pub use equation::*;
+pub use explain::*;
+pub use number::*;
+pub use operation::*;
+pub use precedence::*;
+pub use question_set::*;
+pub use schema::*;
+pub use shape::*;
+pub use values::*;
+pub use weights::*;
+pub use worksheet::*;
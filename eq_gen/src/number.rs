@@ -0,0 +1,186 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+use rand::Rng;
+
+/// A numeric type equations can be built from. Every operation is checked, so the generator
+/// can throw away a candidate instead of overflowing.
+pub trait Number: Clone + Ord + Hash + Debug + Display + Send + Sync + 'static {
+    fn zero() -> Self;
+    fn one() -> Self;
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    /// Division that only succeeds when the result is representable, so exactly for integers
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+
+    /// The value as a float, used to weigh difficulty by number of digits
+    fn to_f64(&self) -> f64;
+    /// The value as a `u64`, if it's a positive whole number and fits
+    fn to_u64(&self) -> Option<u64>;
+
+    /// A uniformly random whole number from `min` to `max` inclusive, `None` if the range is
+    /// empty
+    fn random_between(min: &Self, max: &Self, rng: &mut impl Rng) -> Option<Self>;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+
+            fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *rhs)
+            }
+
+            fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *rhs)
+            }
+
+            fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *rhs)
+            }
+
+            fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                match <$t>::checked_rem(*self, *rhs)? {
+                    0 => <$t>::checked_div(*self, *rhs),
+                    _ => None,
+                }
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                u64::try_from(*self).ok().filter(|n| *n > 0)
+            }
+
+            fn random_between(min: &Self, max: &Self, rng: &mut impl Rng) -> Option<Self> {
+                (min <= max).then(|| rng.random_range(*min..=*max))
+            }
+        }
+    )*};
+}
+
+impl_number!(i16, i32, i64, i128);
+
+#[cfg(feature = "bignum")]
+mod big {
+    use num_bigint::{BigInt, BigUint};
+    use num_integer::Integer;
+    use num_rational::Ratio;
+    use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, ToPrimitive, Zero};
+    use rand::Rng;
+
+    use super::Number;
+
+    // never overflows, so every operation but uneven or zero division succeeds
+    impl Number for BigInt {
+        fn zero() -> Self {
+            Zero::zero()
+        }
+
+        fn one() -> Self {
+            One::one()
+        }
+
+        fn checked_add(&self, rhs: &Self) -> Option<Self> {
+            Some(self + rhs)
+        }
+
+        fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+            Some(self - rhs)
+        }
+
+        fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+            Some(self * rhs)
+        }
+
+        fn checked_div(&self, rhs: &Self) -> Option<Self> {
+            if rhs.is_zero() {
+                return None;
+            }
+            let (quotient, remainder) = self.div_rem(rhs);
+            remainder.is_zero().then_some(quotient)
+        }
+
+        fn to_f64(&self) -> f64 {
+            ToPrimitive::to_f64(self).unwrap_or(f64::INFINITY)
+        }
+
+        fn to_u64(&self) -> Option<u64> {
+            ToPrimitive::to_u64(self).filter(|n| *n > 0)
+        }
+
+        fn random_between(min: &Self, max: &Self, rng: &mut impl Rng) -> Option<Self> {
+            if min > max {
+                return None;
+            }
+            // random bits as wide as the range, tried again whenever they land past its end
+            let span = (max - min).into_parts().1;
+            let bits = span.bits();
+            let mask = (BigUint::one() << bits) - 1u32;
+            loop {
+                let digits: Vec<u32> = (0..bits.div_ceil(32)).map(|_| rng.random()).collect();
+                let offset = BigUint::from_slice(&digits) & &mask;
+                if offset <= span {
+                    return Some(min + BigInt::from(offset));
+                }
+            }
+        }
+    }
+
+    // exact fractions, so division always works out and answers can be fractions of the
+    // whole numbers drawn from the config
+    impl<T> Number for Ratio<T>
+    where
+        T: Number + Integer,
+        Ratio<T>: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
+    {
+        fn zero() -> Self {
+            Ratio::from_integer(<T as Number>::zero())
+        }
+
+        fn one() -> Self {
+            Ratio::from_integer(<T as Number>::one())
+        }
+
+        fn checked_add(&self, rhs: &Self) -> Option<Self> {
+            CheckedAdd::checked_add(self, rhs)
+        }
+
+        fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+            CheckedSub::checked_sub(self, rhs)
+        }
+
+        fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+            CheckedMul::checked_mul(self, rhs)
+        }
+
+        fn checked_div(&self, rhs: &Self) -> Option<Self> {
+            CheckedDiv::checked_div(self, rhs)
+        }
+
+        fn to_f64(&self) -> f64 {
+            self.numer().to_f64() / self.denom().to_f64()
+        }
+
+        fn to_u64(&self) -> Option<u64> {
+            self.is_integer().then(|| self.numer().to_u64()).flatten()
+        }
+
+        fn random_between(min: &Self, max: &Self, rng: &mut impl Rng) -> Option<Self> {
+            let min = min.ceil().to_integer();
+            let max = max.floor().to_integer();
+            T::random_between(&min, &max, rng).map(Ratio::from_integer)
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::cmp;
+use std::fmt::Display;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Number, NumberType, Value, Weights};
+
+/// An arithmetic operation, displayed as its symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OperationType {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Display for OperationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Subtract => write!(f, "-"),
+            Self::Multiply => write!(f, "×"),
+            Self::Divide => write!(f, "÷"),
+        }
+    }
+}
+
+impl OperationType {
+    /// How tightly the operation binds when written without parentheses, multiplication and
+    /// division before addition and subtraction
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide => 2,
+        }
+    }
+
+    /// Works the operation out, `None` if it overflows or divides unevenly
+    pub fn apply<N: Number>(&self, left: &N, right: &N) -> Option<N> {
+        match self {
+            Self::Add => left.checked_add(right),
+            Self::Subtract => left.checked_sub(right),
+            Self::Multiply => left.checked_mul(right),
+            Self::Divide => left.checked_div(right),
+        }
+    }
+}
+
+/// The kind of equations to generate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationConfig<N = i16> {
+    /// The range of the final answer
+    pub answer_min: N,
+    pub answer_max: N,
+    /// The range of values used to calculate the answer
+    pub value_min: N,
+    pub value_max: N,
+    /// How often each kind of number is picked
+    pub allowed_numerics: Weights<NumberType>,
+    /// How often each operation is picked
+    pub allowed_operations: Weights<OperationType>,
+}
+
+impl<N: Number> OperationConfig<N> {
+    /// A random number in the value range, wrapped as a `Value`
+    pub fn rnd_value(&self, rng: &mut impl Rng) -> Value<N> {
+        self.rnd_number(rng).into()
+    }
+
+    /// A random number in the value range, negative only if negatives are allowed
+    pub fn rnd_number(&self, rng: &mut impl Rng) -> N {
+        match self.allowed_numerics.choose(rng, |_| true) {
+            Some(NumberType::Negative) => self.rnd_negative(rng),
+            _ => self.rnd_positive(rng),
+        }
+    }
+
+    /// A random number from zero or the bottom of the value range up to its top
+    pub fn rnd_positive(&self, rng: &mut impl Rng) -> N {
+        let min = cmp::max(N::zero(), self.value_min.clone());
+        let max = cmp::max(N::zero(), self.value_max.clone());
+        N::random_between(&min, &max, rng).unwrap_or_else(N::one)
+    }
+
+    /// A random number anywhere in the value range, taking in zero
+    pub fn rnd_negative(&self, rng: &mut impl Rng) -> N {
+        let min = cmp::min(self.value_min.clone(), N::zero());
+        let max = cmp::max(N::zero(), self.value_max.clone());
+        N::random_between(&min, &max, rng)
+            .or_else(|| N::zero().checked_sub(&N::one()))
+            .unwrap_or_else(N::zero)
+    }
+}
@@ -0,0 +1,155 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Equation, Number, OperationConfig, OperationType, Shape, Template, Value};
+
+// equations tried before giving up on finding one that catches out left-to-right working,
+// as the multiplication in a trap's template can still come out as × 1
+const TRAP_ATTEMPTS: usize = 50;
+
+/// Where an equation is written with parentheses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Parentheses {
+    /// Around every nested equation, so precedence never matters
+    #[default]
+    All,
+    /// Only where the order of operations wouldn't group the equation the same way
+    Needed,
+}
+
+impl<N: Number> Equation<N> {
+    /// The equation written out with the given parentheses
+    pub fn written(&self, parentheses: Parentheses) -> String {
+        match parentheses {
+            Parentheses::All => self.to_string(),
+            Parentheses::Needed => {
+                let left = written_operand(&self.left, self.op, false);
+                let right = written_operand(&self.right, self.op, true);
+                format!("{left} {} {right}", self.op)
+            }
+        }
+    }
+
+    /// The answer reached by working the equation as written with only the needed
+    /// parentheses strictly from left to right, ignoring precedence. `None` if that goes
+    /// wrong along the way, eg. dividing unevenly.
+    pub fn left_to_right(&self) -> Option<N> {
+        let mut operands = Vec::new();
+        let mut ops = Vec::new();
+        flatten(self, &mut operands, &mut ops);
+        let mut operands = operands.into_iter();
+        let first = operands.next()??;
+        ops.into_iter()
+            .zip(operands)
+            .try_fold(first, |acc, (op, operand)| op.apply(&acc, &operand?))
+    }
+
+    /// Whether working left to right instead of by precedence gets the wrong answer
+    pub fn is_precedence_trap(&self) -> bool {
+        self.left_to_right().as_ref() != Some(&self.answer)
+    }
+
+    /// Generates an equation of `op_count` operations that is only answered right by
+    /// following the order of operations, such as `2 + 3 × 4`
+    pub fn rnd_precedence_trap(
+        op_config: &OperationConfig<N>,
+        op_count: u8,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        if op_count < 2 {
+            return Err("order of operations needs at least two operations".to_string());
+        }
+        let ops = &op_config.allowed_operations;
+        let additive = ops.contains(&OperationType::Add) || ops.contains(&OperationType::Subtract);
+        let multiplicative =
+            ops.contains(&OperationType::Multiply) || ops.contains(&OperationType::Divide);
+        if !additive || !multiplicative {
+            return Err("order of operations needs both + or - and × or ÷ allowed".to_string());
+        }
+
+        let shape = Shape::Random(op_count);
+        for _ in 0..TRAP_ATTEMPTS {
+            let template = Shape::Template(trap_template(op_config, op_count, rng));
+            if let Ok(eq) = Self::rnd_shaped(op_config, &template, rng)
+                && eq.is_precedence_trap()
+            {
+                return Ok(eq);
+            }
+        }
+        Err(format!(
+            "no equation of {shape} that needs the order of operations fits the config"
+        ))
+    }
+}
+
+// a × or ÷ on the right of a + or -, ie. `_ + _ × _`, which working left to right gets wrong
+// unless the multiplication does nothing, with the rest of the operations added anywhere
+fn trap_template<N: Number>(
+    op_config: &OperationConfig<N>,
+    op_count: u8,
+    rng: &mut impl Rng,
+) -> Template {
+    let ops = &op_config.allowed_operations;
+    let additive = ops.choose(rng, |op| op.precedence() == 1);
+    let multiplicative = ops.choose(rng, |op| op.precedence() == 2);
+    let mut template = Template::Operation {
+        op: additive,
+        left: Box::new(Template::Number),
+        right: Box::new(Template::Operation {
+            op: multiplicative,
+            left: Box::new(Template::Number),
+            right: Box::new(Template::Number),
+        }),
+    };
+    template.grow(op_count - 2, rng);
+    template
+}
+
+// a nested equation keeps its parentheses when precedence alone would group it differently:
+// when it binds more loosely than the operation it's part of, or as the right side of a
+// subtraction or division at the same level, ie. 8 - (3 + 2) or 12 ÷ (6 ÷ 2)
+pub(crate) fn needs_parentheses<N: Number>(
+    value: &Value<N>,
+    parent: OperationType,
+    right: bool,
+) -> bool {
+    let Value::Equation(eq) = value else {
+        return false;
+    };
+    let same_level = eq.op.precedence() == parent.precedence();
+    eq.op.precedence() < parent.precedence()
+        || (right
+            && same_level
+            && matches!(parent, OperationType::Subtract | OperationType::Divide))
+}
+
+fn written_operand<N: Number>(value: &Value<N>, parent: OperationType, right: bool) -> String {
+    match value {
+        Value::Number(n) => n.to_string(),
+        Value::Equation(eq) if needs_parentheses(value, parent, right) => {
+            format!("({})", eq.written(Parentheses::Needed))
+        }
+        Value::Equation(eq) => eq.written(Parentheses::Needed),
+    }
+}
+
+// the operands and operations as they read from left to right, each parenthesized group
+// worked out on its own
+fn flatten<N: Number>(
+    eq: &Equation<N>,
+    operands: &mut Vec<Option<N>>,
+    ops: &mut Vec<OperationType>,
+) {
+    for (value, right) in [(eq.left.as_ref(), false), (eq.right.as_ref(), true)] {
+        if right {
+            ops.push(eq.op);
+        }
+        match value {
+            Value::Number(n) => operands.push(Some(n.clone())),
+            Value::Equation(nested) if needs_parentheses(value, eq.op, right) => {
+                operands.push(nested.left_to_right());
+            }
+            Value::Equation(nested) => flatten(nested, operands, ops),
+        }
+    }
+}
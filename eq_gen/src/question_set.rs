@@ -0,0 +1,68 @@
+use rand::SeedableRng;
+use rand::prelude::IteratorRandom;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Equation, Number, OperationConfig, Shape};
+
+// gives up on a set that can't be filled after this many tries per equation
+const ATTEMPTS_PER_EQUATION: usize = 4;
+
+/// A set of generated equations with the config and seed they came from, so the same
+/// questions can be made again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionSet<N = i16> {
+    pub config: OperationConfig<N>,
+    pub seed: u64,
+    pub equations: Vec<Equation<N>>,
+}
+
+impl<N: Number> QuestionSet<N> {
+    /// Generates up to `count` equations of 1 to `max_operations` operations each. The same
+    /// config and seed always give the same set.
+    pub fn generate(
+        config: OperationConfig<N>,
+        max_operations: u8,
+        count: usize,
+        seed: u64,
+    ) -> Self {
+        Self::generate_with(config, count, seed, |rng| {
+            Shape::Random((1..=max_operations.max(1)).choose(rng).unwrap())
+        })
+    }
+
+    /// Generates up to `count` equations all laid out as `shape`
+    pub fn generate_shaped(
+        config: OperationConfig<N>,
+        shape: &Shape,
+        count: usize,
+        seed: u64,
+    ) -> Self {
+        Self::generate_with(config, count, seed, |_| shape.clone())
+    }
+
+    fn generate_with(
+        config: OperationConfig<N>,
+        count: usize,
+        seed: u64,
+        mut shape: impl FnMut(&mut ChaCha8Rng) -> Shape,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut equations: Vec<Equation<N>> = Vec::with_capacity(count);
+        for _ in 0..count * ATTEMPTS_PER_EQUATION {
+            if equations.len() == count {
+                break;
+            }
+            let shape = shape(&mut rng);
+            if let Ok(eq) = Equation::rnd_shaped(&config, &shape, &mut rng) {
+                equations.push(eq);
+            }
+        }
+
+        Self {
+            config,
+            seed,
+            equations,
+        }
+    }
+}
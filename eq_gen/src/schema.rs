@@ -0,0 +1,41 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+/// Version of the serialized eq_gen model. Bump it whenever a change to `Equation`, `Value`,
+/// `OperationType`, `NumberType` or `OperationConfig` would stop older documents from loading.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Any part of the model stored alongside the schema version it was written with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema: u32,
+    pub data: T,
+}
+
+impl<T: Serialize + DeserializeOwned> Versioned<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            schema: SCHEMA_VERSION,
+            data,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Reads a document, checking its schema before the data so a newer one fails clearly
+    pub fn from_json(text: &str) -> Result<T, String> {
+        let json: Json = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let schema = json
+            .get("schema")
+            .and_then(Json::as_u64)
+            .ok_or("missing schema version")?;
+        if schema == 0 || schema > SCHEMA_VERSION as u64 {
+            return Err(format!("unsupported schema version {schema}"));
+        }
+        let data = json.get("data").cloned().ok_or("missing data")?;
+        serde_json::from_value(data).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use rand::Rng;
+
+use crate::OperationType;
+
+/// How the operations of a generated equation nest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// Any shape with this many operations
+    Random(u8),
+    /// Each operation on the left of the next, ie. ((a+b)+c)+d
+    LeftChain(u8),
+    /// Each operation on the right of the next, ie. a+(b+(c+d))
+    RightChain(u8),
+    /// Operations split as evenly as they can be between both sides, ie. (a+b)+(c+d)
+    Balanced(u8),
+    /// Exactly the given shape
+    Template(Template),
+}
+
+impl Shape {
+    /// The template to fill, picking one at random for `Shape::Random`
+    pub fn template(&self, rng: &mut impl Rng) -> Template {
+        match self {
+            Self::Random(op_count) => {
+                let mut template = Template::Number;
+                template.grow(*op_count, rng);
+                template
+            }
+            Self::LeftChain(op_count) => (0..*op_count).fold(Template::Number, |chain, _| {
+                Template::any(chain, Template::Number)
+            }),
+            Self::RightChain(op_count) => (0..*op_count).fold(Template::Number, |chain, _| {
+                Template::any(Template::Number, chain)
+            }),
+            Self::Balanced(op_count) => Template::balanced(*op_count),
+            Self::Template(template) => template.clone(),
+        }
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Random(op_count) => write!(f, "{op_count} operations"),
+            Self::LeftChain(op_count) => write!(f, "{op_count} operations chained to the left"),
+            Self::RightChain(op_count) => write!(f, "{op_count} operations chained to the right"),
+            Self::Balanced(op_count) => write!(f, "{op_count} balanced operations"),
+            Self::Template(template) => write!(f, "the shape {template}"),
+        }
+    }
+}
+
+/// The outline of an equation, written like `(_ op _) × _`: `_` is a number, `op` any allowed
+/// operation and `+ - × ÷` a fixed one. Operations outside parentheses group left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Template {
+    Number,
+    Operation {
+        // `None` for any allowed operation
+        op: Option<OperationType>,
+        left: Box<Template>,
+        right: Box<Template>,
+    },
+}
+
+impl Template {
+    fn any(left: Template, right: Template) -> Self {
+        Self::Operation {
+            op: None,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    fn balanced(op_count: u8) -> Self {
+        match op_count {
+            0 => Self::Number,
+            _ => {
+                let rest = op_count - 1;
+                Self::any(Self::balanced(rest - rest / 2), Self::balanced(rest / 2))
+            }
+        }
+    }
+
+    pub fn op_count(&self) -> usize {
+        match self {
+            Self::Number => 0,
+            Self::Operation { left, right, .. } => 1 + left.op_count() + right.op_count(),
+        }
+    }
+
+    fn number_count(&self) -> usize {
+        match self {
+            Self::Number => 1,
+            Self::Operation { left, right, .. } => left.number_count() + right.number_count(),
+        }
+    }
+
+    // adds `op_count` operations, each in place of a number picked at random
+    pub(crate) fn grow(&mut self, op_count: u8, rng: &mut impl Rng) {
+        for _ in 0..op_count {
+            let slot = rng.random_range(0..self.number_count());
+            self.split(slot);
+        }
+    }
+
+    // turns the nth number, counting from the left, into an operation on two numbers
+    fn split(&mut self, n: usize) {
+        match self {
+            Self::Number => *self = Self::any(Self::Number, Self::Number),
+            Self::Operation { left, right, .. } => {
+                let left_count = left.number_count();
+                if n < left_count {
+                    left.split(n);
+                } else {
+                    right.split(n - left_count);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number => write!(f, "_"),
+            Self::Operation { op, left, right } => {
+                write_operand(f, left)?;
+                match op {
+                    Some(op) => write!(f, " {op} ")?,
+                    None => write!(f, " op ")?,
+                }
+                write_operand(f, right)
+            }
+        }
+    }
+}
+
+fn write_operand(f: &mut std::fmt::Formatter<'_>, template: &Template) -> std::fmt::Result {
+    match template {
+        Template::Number => write!(f, "_"),
+        nested => write!(f, "({nested})"),
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars().peekable();
+        let template = parse_chain(&mut chars)?;
+        skip_spaces(&mut chars);
+        if let Some(c) = chars.next() {
+            return Err(format!("unexpected `{c}` in template"));
+        }
+        // the other shapes count their operations in a u8 too
+        match template.op_count() {
+            count if count > u8::MAX as usize => Err(format!(
+                "template has {count} operations, no more than {} fit",
+                u8::MAX
+            )),
+            _ => Ok(template),
+        }
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+// numbers and parenthesized groups joined by operations, grouping left to right
+fn parse_chain(chars: &mut Peekable<Chars>) -> Result<Template, String> {
+    let mut template = parse_operand(chars)?;
+    loop {
+        skip_spaces(chars);
+        let op = match chars.peek() {
+            Some('o') => {
+                chars.next();
+                if chars.next() != Some('p') {
+                    return Err("expected `op` in template".to_string());
+                }
+                None
+            }
+            Some('+') => Some(OperationType::Add),
+            Some('-') => Some(OperationType::Subtract),
+            Some('×' | 'x' | '*') => Some(OperationType::Multiply),
+            Some('÷' | '/') => Some(OperationType::Divide),
+            _ => return Ok(template),
+        };
+        if op.is_some() {
+            chars.next();
+        }
+        let right = parse_operand(chars)?;
+        template = Template::Operation {
+            op,
+            left: Box::new(template),
+            right: Box::new(right),
+        };
+    }
+}
+
+fn parse_operand(chars: &mut Peekable<Chars>) -> Result<Template, String> {
+    skip_spaces(chars);
+    match chars.next() {
+        Some('_') => Ok(Template::Number),
+        Some('(') => {
+            let template = parse_chain(chars)?;
+            skip_spaces(chars);
+            match chars.next() {
+                Some(')') => Ok(template),
+                _ => Err("missing `)` in template".to_string()),
+            }
+        }
+        Some(c) => Err(format!("expected `_` or `(` in template, found `{c}`")),
+        None => Err("template ends early".to_string()),
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::equation::Equation;
+use crate::number::Number;
+
+/// A kind of number an equation may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum NumberType {
+    Whole,
+    Negative,
+}
+
+/// One side of an equation: a plain number or a nested equation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Value<N = i16> {
+    Number(N),
+    Equation(Equation<N>),
+}
+
+impl<N: Number> Value<N> {
+    /// The number itself, or the answer of a nested equation
+    pub fn to_number(&self) -> N {
+        match self {
+            Self::Number(v) => v.clone(),
+            Self::Equation(e) => e.answer.clone(),
+        }
+    }
+
+    pub fn difficulty(&self) -> u16 {
+        match self {
+            Self::Number(n) => {
+                let digits = (n.to_f64().log10().floor() as u16) + 1;
+
+                let sign = if *n < N::zero() { 10 } else { 1 };
+                digits * sign
+            }
+            Self::Equation(e) => e.difficulty(),
+        }
+    }
+}
+
+impl<N: Number> From<N> for Value<N> {
+    fn from(value: N) -> Self {
+        Value::Number(value)
+    }
+}
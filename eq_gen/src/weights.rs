@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+/// How likely each allowed choice is relative to the others, eg. `Add` 60, `Subtract` 30 and
+/// `Multiply` 10. Choices are kept in a fixed order, so the same seed always picks the same
+/// way on every run and platform. A choice with a weight of zero is never picked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "WeightsDoc<T>")]
+pub struct Weights<T: Ord>(BTreeMap<T, u32>);
+
+impl<T: Ord + Copy> Weights<T> {
+    /// Sets the weight of a choice, replacing any it had
+    pub fn insert(&mut self, choice: T, weight: u32) {
+        self.0.insert(choice, weight);
+    }
+
+    pub fn weight(&self, choice: &T) -> u32 {
+        self.0.get(choice).copied().unwrap_or(0)
+    }
+
+    /// Whether the choice can be picked at all
+    pub fn contains(&self, choice: &T) -> bool {
+        self.weight(choice) > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(|w| *w == 0)
+    }
+
+    /// Every choice that can be picked with its weight, in order
+    pub fn iter(&self) -> impl Iterator<Item = (T, u32)> + '_ {
+        self.0
+            .iter()
+            .filter(|(_, w)| **w > 0)
+            .map(|(c, w)| (*c, *w))
+    }
+
+    /// Picks a choice by weight from those `allowed` lets through
+    pub fn choose(&self, rng: &mut impl Rng, allowed: impl Fn(&T) -> bool) -> Option<T> {
+        let choices: Vec<(T, u32)> = self.iter().filter(|(c, _)| allowed(c)).collect();
+        choices
+            .choose_weighted(rng, |(_, w)| *w)
+            .ok()
+            .map(|(c, _)| *c)
+    }
+}
+
+impl<T: Ord> Default for Weights<T> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+/// Every choice equally likely
+impl<T: Ord> FromIterator<T> for Weights<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|c| (c, 1)).collect())
+    }
+}
+
+impl<T: Ord> FromIterator<(T, u32)> for Weights<T> {
+    fn from_iter<I: IntoIterator<Item = (T, u32)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord, const L: usize> From<[(T, u32); L]> for Weights<T> {
+    fn from(weights: [(T, u32); L]) -> Self {
+        weights.into_iter().collect()
+    }
+}
+
+// documents from before weights listed the allowed choices, each as likely as the others
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WeightsDoc<T: Ord> {
+    Weighted(BTreeMap<T, u32>),
+    Listed(Vec<T>),
+}
+
+impl<T: Ord> From<WeightsDoc<T>> for Weights<T> {
+    fn from(doc: WeightsDoc<T>) -> Self {
+        match doc {
+            WeightsDoc::Weighted(weights) => Self(weights),
+            WeightsDoc::Listed(choices) => choices.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Number, OperationConfig, Parentheses, QuestionSet};
+
+/// A printable sheet of equations, written out as HTML with or without the answers
+#[derive(Debug, Clone)]
+pub struct Worksheet<N = i16> {
+    pub title: String,
+    pub set: QuestionSet<N>,
+    pub columns: usize,
+    pub parentheses: Parentheses,
+}
+
+impl<N: Number> Worksheet<N> {
+    pub const DEFAULT_COLUMNS: usize = 3;
+
+    /// Generates up to `count` equations of up to `max_operations` each, the same seed always
+    /// giving the same sheet
+    pub fn generate(
+        config: OperationConfig<N>,
+        max_operations: u8,
+        count: usize,
+        seed: u64,
+    ) -> Self {
+        Self::from_set(QuestionSet::generate(config, max_operations, count, seed))
+    }
+
+    /// A sheet of equations already generated
+    pub fn from_set(set: QuestionSet<N>) -> Self {
+        Self {
+            title: "Captain's Calculations".to_string(),
+            set,
+            columns: Self::DEFAULT_COLUMNS,
+            parentheses: Parentheses::All,
+        }
+    }
+
+    /// The sheet as a standalone HTML page, with the answers filled in for the key
+    pub fn to_html(&self, answer_key: bool) -> String {
+        let title = if answer_key {
+            format!("{} - Answer key", self.title)
+        } else {
+            self.title.clone()
+        };
+        let problems = self
+            .set
+            .equations
+            .iter()
+            .map(|eq| {
+                let answer = if answer_key {
+                    format!("<b>{}</b>", eq.answer)
+                } else {
+                    "<span class=\"blank\"></span>".to_string()
+                };
+                let equation = eq.written(self.parentheses);
+                format!("    <li>{} = {answer}</li>\n", escape(&equation))
+            })
+            .collect::<String>();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+  body {{ font-family: Georgia, serif; color: #33323d; margin: 2em; }}
+  header {{ border: 4px double #7a4a1e; background: #f4e4bc; padding: 0.5em 1em; text-align: center; }}
+  header h1 {{ margin: 0.2em 0; letter-spacing: 0.05em; }}
+  header .crossbones {{ font-size: 1.6em; }}
+  .details {{ display: flex; justify-content: space-between; margin: 1.5em 0; }}
+  .details span {{ display: inline-block; min-width: 14em; border-bottom: 1px solid #33323d; }}
+  ol {{ columns: {columns}; column-gap: 3em; font-size: 1.3em; line-height: 2.4em; }}
+  li {{ break-inside: avoid; }}
+  .blank {{ display: inline-block; width: 3em; border-bottom: 1px solid #33323d; }}
+  footer {{ margin-top: 2em; font-size: 0.8em; color: #7a4a1e; text-align: center; }}
+  @media print {{ body {{ margin: 0; }} }}
+</style>
+</head>
+<body>
+<header>
+  <div class="crossbones">&#9760; &#9875; &#9760;</div>
+  <h1>{title}</h1>
+  <div>Solve every sum to claim yer loot!</div>
+</header>
+<div class="details"><div>Name: <span></span></div><div>Date: <span></span></div></div>
+<ol>
+{problems}</ol>
+<footer>Calculoot worksheet, seed {seed}</footer>
+</body>
+</html>
+"#,
+            title = escape(&title),
+            columns = self.columns.max(1),
+            seed = self.set.seed,
+        )
+    }
+
+    /// The file name of the sheet or its answer key
+    pub fn file_name(&self, answer_key: bool) -> String {
+        match answer_key {
+            false => format!("worksheet-{}.html", self.set.seed),
+            true => format!("worksheet-{}-answers.html", self.set.seed),
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,454 @@
+use eq_gen::{
+    Equation, Number, NumberType, OperationConfig, OperationType, Parentheses, QuestionSet,
+    SCHEMA_VERSION, Shape, Template, Value, Versioned, Weights,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn config(ops: &[OperationType]) -> OperationConfig {
+    OperationConfig {
+        answer_min: 1,
+        answer_max: 20,
+        value_min: 0,
+        value_max: 20,
+        allowed_numerics: [NumberType::Whole].into_iter().collect(),
+        allowed_operations: ops.iter().copied().collect(),
+    }
+}
+
+fn eval<N: Number>(value: &Value<N>) -> N {
+    match value {
+        Value::Number(n) => n.clone(),
+        Value::Equation(eq) => {
+            let (left, right) = (eval(&eq.left), eval(&eq.right));
+            match eq.op {
+                OperationType::Add => left.checked_add(&right),
+                OperationType::Subtract => left.checked_sub(&right),
+                OperationType::Multiply => left.checked_mul(&right),
+                OperationType::Divide => left.checked_div(&right),
+            }
+            .unwrap()
+        }
+    }
+}
+
+fn all_operations() -> Weights<OperationType> {
+    [
+        OperationType::Add,
+        OperationType::Subtract,
+        OperationType::Multiply,
+        OperationType::Divide,
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[test]
+fn answers_match_the_equation() {
+    let mut config = config(&[]);
+    config.allowed_operations = all_operations();
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    for op_count in 1..=3 {
+        for _ in 0..200 {
+            let Some(eq) = Equation::rnd_compound(&config, op_count, &mut rng) else {
+                continue;
+            };
+            assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer, "{eq}");
+            assert!((config.answer_min..=config.answer_max).contains(&eq.answer));
+        }
+    }
+}
+
+#[test]
+fn large_numbers_never_overflow() {
+    let config = OperationConfig {
+        answer_min: i16::MAX - 100,
+        answer_max: i16::MAX,
+        value_min: i16::MIN,
+        value_max: i16::MAX,
+        allowed_numerics: [NumberType::Whole, NumberType::Negative]
+            .into_iter()
+            .collect(),
+        allowed_operations: all_operations(),
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(11);
+    for _ in 0..500 {
+        if let Some(eq) = Equation::rnd_compound(&config, 3, &mut rng) {
+            assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer, "{eq}");
+        }
+    }
+}
+
+#[test]
+fn wider_numbers_reach_four_digits() {
+    let config = OperationConfig::<i64> {
+        answer_min: 1_000,
+        answer_max: 9_999,
+        value_min: 1_000,
+        value_max: 9_999,
+        allowed_numerics: [NumberType::Whole].into_iter().collect(),
+        allowed_operations: [OperationType::Subtract].into_iter().collect(),
+    };
+    let set = QuestionSet::generate(config, 1, 10, 5);
+    assert!(!set.equations.is_empty());
+    for eq in &set.equations {
+        assert!(eq.answer >= 1_000);
+        assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer);
+    }
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn big_integers_go_past_i128() {
+    use num_bigint::BigInt;
+
+    let big = BigInt::from(10).pow(40);
+    let config = OperationConfig::<BigInt> {
+        answer_min: big.clone(),
+        answer_max: &big * 2,
+        value_min: BigInt::from(1),
+        value_max: &big * 4,
+        allowed_numerics: [NumberType::Whole].into_iter().collect(),
+        allowed_operations: all_operations(),
+    };
+    let set = QuestionSet::generate(config, 2, 20, 3);
+    assert!(!set.equations.is_empty());
+    for eq in &set.equations {
+        assert!(eq.answer >= big);
+        assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer, "{eq}");
+    }
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn rationals_divide_into_fractions() {
+    use num_rational::Ratio;
+
+    let whole = |n: i64| Ratio::from_integer(n);
+    let config = OperationConfig::<Ratio<i64>> {
+        answer_min: whole(1),
+        answer_max: whole(20),
+        value_min: whole(0),
+        value_max: whole(20),
+        allowed_numerics: [NumberType::Whole].into_iter().collect(),
+        allowed_operations: [OperationType::Multiply].into_iter().collect(),
+    };
+    let set = QuestionSet::generate(config, 1, 50, 4);
+    assert!(
+        set.equations
+            .iter()
+            .any(|eq| !eq.right.to_number().is_integer())
+    );
+    for eq in &set.equations {
+        assert!(eq.answer.is_integer());
+        assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer, "{eq}");
+    }
+}
+
+#[test]
+fn only_allowed_operations_are_used() {
+    let config = config(&[OperationType::Add]);
+    let set = QuestionSet::generate(config, 3, 30, 1);
+    assert_eq!(set.equations.len(), 30);
+    for eq in &set.equations {
+        assert!(eq.operations().iter().all(|op| *op == OperationType::Add));
+    }
+}
+
+#[test]
+fn the_same_seed_gives_the_same_set() {
+    let mut config = config(&[]);
+    config.allowed_operations = all_operations();
+    let first = QuestionSet::generate(config.clone(), 3, 30, 9);
+    let again = QuestionSet::generate(config, 3, 30, 9);
+    assert_eq!(first, again);
+}
+
+#[test]
+fn operations_follow_their_weights() {
+    let mut config = config(&[]);
+    config.allowed_operations = [(OperationType::Add, 9), (OperationType::Subtract, 1)].into();
+    let set = QuestionSet::generate(config, 1, 200, 4);
+    let adds = set
+        .equations
+        .iter()
+        .filter(|eq| eq.op == OperationType::Add)
+        .count();
+    assert!(
+        adds > set.equations.len() * 3 / 4,
+        "{adds} of {}",
+        set.equations.len()
+    );
+}
+
+#[test]
+fn listed_operations_load_as_equally_likely() {
+    let json = r#"{"answer_min":1,"answer_max":20,"value_min":0,"value_max":20,
+        "allowed_numerics":["Whole"],"allowed_operations":["Add","Divide"]}"#;
+    let config: OperationConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(config.allowed_operations.weight(&OperationType::Add), 1);
+    assert_eq!(config.allowed_operations.weight(&OperationType::Divide), 1);
+    assert!(!config.allowed_operations.contains(&OperationType::Multiply));
+}
+
+#[test]
+fn shapes_have_exactly_their_operations() {
+    let mut config = config(&[OperationType::Add, OperationType::Subtract]);
+    config.answer_max = 50;
+    config.value_max = 50;
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    for op_count in 1..=4 {
+        for shape in [
+            Shape::Random(op_count),
+            Shape::LeftChain(op_count),
+            Shape::RightChain(op_count),
+            Shape::Balanced(op_count),
+        ] {
+            let eq = Equation::rnd_shaped(&config, &shape, &mut rng).unwrap();
+            assert_eq!(eq.operations().len(), op_count as usize, "{shape}: {eq}");
+            assert_eq!(eval(&Value::Equation(eq.clone())), eq.answer);
+        }
+    }
+}
+
+#[test]
+fn balanced_shapes_nest_on_both_sides() {
+    let template = Shape::Balanced(3).template(&mut ChaCha8Rng::seed_from_u64(0));
+    assert_eq!(template, "(_ op _) op (_ op _)".parse().unwrap());
+
+    let config = config(&[OperationType::Add, OperationType::Subtract]);
+    let shape = Shape::Balanced(3);
+    let eq = Equation::rnd_shaped(&config, &shape, &mut ChaCha8Rng::seed_from_u64(3)).unwrap();
+    assert!(matches!(*eq.left, Value::Equation(_)), "{eq}");
+    assert!(matches!(*eq.right, Value::Equation(_)), "{eq}");
+}
+
+#[test]
+fn templates_fix_their_operations() {
+    let template: Template = "(_ + _) × _".parse().unwrap();
+    assert_eq!(template.op_count(), 2);
+    assert_eq!(template.to_string(), "(_ + _) × _");
+
+    let config = config(&[OperationType::Add, OperationType::Multiply]);
+    let mut rng = ChaCha8Rng::seed_from_u64(8);
+    let eq = Equation::rnd_shaped(&config, &Shape::Template(template), &mut rng).unwrap();
+    assert_eq!(eq.op, OperationType::Multiply);
+    match eq.left.as_ref() {
+        Value::Equation(left) => assert_eq!(left.op, OperationType::Add),
+        Value::Number(_) => panic!("{eq} should start with a nested addition"),
+    }
+}
+
+#[test]
+fn bad_templates_and_impossible_shapes_are_errors() {
+    assert!("(_ + _".parse::<Template>().is_err());
+    assert!("_ +".parse::<Template>().is_err());
+    assert!("_ _".parse::<Template>().is_err());
+    let longest = vec!["_"; 256].join(" + ");
+    assert_eq!(longest.parse::<Template>().unwrap().op_count(), 255);
+    let too_long = vec!["_"; 257].join(" + ");
+    assert!(too_long.parse::<Template>().is_err());
+
+    let mut config = config(&[OperationType::Add]);
+    config.answer_min = 30;
+    config.answer_max = 30;
+    config.value_max = 5;
+    let mut rng = ChaCha8Rng::seed_from_u64(6);
+    assert!(Equation::rnd_shaped(&config, &Shape::LeftChain(1), &mut rng).is_err());
+    assert!(Equation::rnd_shaped(&config, &Shape::Random(0), &mut rng).is_err());
+}
+
+#[test]
+fn only_needed_parentheses_are_written() {
+    let times: Equation = Equation::new(3.into(), OperationType::Multiply, 4.into(), 12);
+    let plus: Equation = Equation::new(3.into(), OperationType::Add, 4.into(), 7);
+    let cases = [
+        (
+            Value::Number(2),
+            OperationType::Add,
+            Value::Equation(times.clone()),
+            "2 + 3 × 4",
+        ),
+        (
+            Value::Equation(plus.clone()),
+            OperationType::Multiply,
+            Value::Number(2),
+            "(3 + 4) × 2",
+        ),
+        (
+            Value::Number(9),
+            OperationType::Subtract,
+            Value::Equation(plus.clone()),
+            "9 - (3 + 4)",
+        ),
+        (
+            Value::Equation(plus),
+            OperationType::Subtract,
+            Value::Number(2),
+            "3 + 4 - 2",
+        ),
+        (
+            Value::Number(24),
+            OperationType::Divide,
+            Value::Equation(times),
+            "24 ÷ (3 × 4)",
+        ),
+    ];
+    for (left, op, right, written) in cases {
+        let eq = Equation::new(left, op, right, 0);
+        assert_eq!(eq.written(Parentheses::Needed), written);
+        assert_eq!(eq.written(Parentheses::All), eq.to_string());
+    }
+}
+
+#[test]
+fn left_to_right_ignores_precedence() {
+    let times: Equation = Equation::new(3.into(), OperationType::Multiply, 4.into(), 12);
+    let eq = Equation::new(
+        2.into(),
+        OperationType::Add,
+        Value::Equation(times.clone()),
+        14,
+    );
+    assert_eq!(eq.left_to_right(), Some(20));
+    assert!(eq.is_precedence_trap());
+
+    let eq = Equation::new(Value::Equation(times), OperationType::Add, 2.into(), 14);
+    assert_eq!(eq.left_to_right(), Some(14));
+    assert!(!eq.is_precedence_trap());
+}
+
+#[test]
+fn precedence_traps_catch_left_to_right_working() {
+    let mixed = config(&[OperationType::Add, OperationType::Multiply]);
+    for seed in 0..20 {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        for op_count in 2..=4 {
+            let eq = Equation::rnd_precedence_trap(&mixed, op_count, &mut rng).unwrap();
+            assert_eq!(eq.operations().len(), op_count as usize);
+            let written = eq.written(Parentheses::Needed);
+            assert_ne!(eq.left_to_right(), Some(eq.answer), "{written}");
+        }
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(12);
+
+    let adding_only = config(&[OperationType::Add]);
+    assert!(Equation::rnd_precedence_trap(&adding_only, 2, &mut rng).is_err());
+    assert!(Equation::rnd_precedence_trap(&mixed, 1, &mut rng).is_err());
+}
+
+#[test]
+fn steps_work_one_operation_at_a_time() {
+    let times: Equation = Equation::new(3.into(), OperationType::Multiply, 4.into(), 12);
+    let plus: Equation = Equation::new(1.into(), OperationType::Add, 2.into(), 3);
+    let left = Equation::new(Value::Equation(plus), OperationType::Add, 5.into(), 8);
+    let eq = Equation::new(
+        Value::Equation(left),
+        OperationType::Add,
+        Value::Equation(times),
+        20,
+    );
+
+    let written = |parentheses| {
+        eq.steps(parentheses)
+            .iter()
+            .map(|step| step.remaining.written(parentheses))
+            .collect::<Vec<_>>()
+    };
+    // with every nested equation in parentheses they go from the left
+    let all = ["(3 + 5) + (3 × 4)", "8 + (3 × 4)", "8 + 12", "20"];
+    assert_eq!(written(Parentheses::All), all);
+    // multiplication goes before addition once the parentheses are gone
+    let needed = ["1 + 2 + 5 + 12", "3 + 5 + 12", "8 + 12", "20"];
+    assert_eq!(written(Parentheses::Needed), needed);
+
+    // without parentheses each level goes from the left, whichever way the equation was built
+    let minus: Equation = Equation::new(5.into(), OperationType::Subtract, 3.into(), 2);
+    let eq = Equation::new(2.into(), OperationType::Add, Value::Equation(minus), 4);
+    let steps = eq.steps(Parentheses::Needed);
+    let written: Vec<_> = steps
+        .iter()
+        .map(|step| step.remaining.written(Parentheses::Needed))
+        .collect();
+    assert_eq!(written, ["7 - 3", "4"]);
+
+    let divide: Equation = Equation::new(6.into(), OperationType::Divide, 3.into(), 2);
+    let eq = Equation::new(
+        2.into(),
+        OperationType::Multiply,
+        Value::Equation(divide),
+        4,
+    );
+    let steps = eq.steps(Parentheses::Needed);
+    let written: Vec<_> = steps
+        .iter()
+        .map(|step| step.remaining.written(Parentheses::Needed))
+        .collect();
+    assert_eq!(written, ["12 ÷ 3", "4"]);
+}
+
+#[test]
+fn steps_end_with_the_answer() {
+    let mixed = config(&[
+        OperationType::Add,
+        OperationType::Subtract,
+        OperationType::Multiply,
+    ]);
+    let set = QuestionSet::generate(mixed, 3, 30, 10);
+    for eq in &set.equations {
+        for parentheses in [Parentheses::All, Parentheses::Needed] {
+            let steps = eq.steps(parentheses);
+            assert_eq!(steps.len(), eq.operations().len());
+            for step in &steps {
+                assert_eq!(
+                    eval(&Value::Equation(step.worked.clone())),
+                    step.worked.answer
+                );
+            }
+            assert_eq!(steps.last().unwrap().remaining, Value::Number(eq.answer));
+        }
+    }
+}
+
+#[test]
+fn canonical_orders_commutative_operands() {
+    let eq: Equation = Equation::new(4.into(), OperationType::Add, 3.into(), 7);
+    let swapped: Equation = Equation::new(3.into(), OperationType::Add, 4.into(), 7);
+    assert_eq!(eq.canonical(), swapped.canonical());
+
+    let eq: Equation = Equation::new(7.into(), OperationType::Subtract, 3.into(), 4);
+    assert_eq!(eq.canonical(), eq);
+}
+
+#[test]
+fn versioned_sets_round_trip() {
+    let set = QuestionSet::generate(config(&[OperationType::Add]), 2, 5, 3);
+    let json = Versioned::new(set.clone()).to_json().unwrap();
+    assert_eq!(Versioned::<QuestionSet>::from_json(&json).unwrap(), set);
+
+    let newer = json.replacen(
+        &format!("\"schema\":{SCHEMA_VERSION}"),
+        &format!("\"schema\":{}", SCHEMA_VERSION + 1),
+        1,
+    );
+    assert!(Versioned::<QuestionSet>::from_json(&newer).is_err());
+}
+
+#[test]
+fn equations_and_configs_round_trip_through_json() {
+    let mut config = config(&[]);
+    config.allowed_operations = all_operations();
+    config.allowed_numerics = [(NumberType::Whole, 3), (NumberType::Negative, 1)].into();
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        serde_json::from_str::<OperationConfig>(&json).unwrap(),
+        config
+    );
+
+    let set = QuestionSet::generate(config, 3, 20, 8);
+    for eq in &set.equations {
+        let json = serde_json::to_string(eq).unwrap();
+        assert_eq!(serde_json::from_str::<Equation>(&json).unwrap(), *eq);
+    }
+}
@@ -0,0 +1,64 @@
+use eq_gen::{NumberType, OperationConfig, OperationType, Parentheses, Worksheet};
+
+fn worksheet() -> Worksheet {
+    let config = OperationConfig {
+        answer_min: 1,
+        answer_max: 20,
+        value_min: 0,
+        value_max: 20,
+        allowed_numerics: [NumberType::Whole].into_iter().collect(),
+        allowed_operations: [OperationType::Add, OperationType::Multiply]
+            .into_iter()
+            .collect(),
+    };
+    Worksheet::generate(config, 3, 12, 5)
+}
+
+// the text of each numbered problem, in order
+fn problems(html: &str) -> Vec<&str> {
+    html.lines()
+        .filter_map(|line| line.trim().strip_prefix("<li>")?.strip_suffix("</li>"))
+        .collect()
+}
+
+#[test]
+fn the_answer_key_matches_the_sheet() {
+    let worksheet = worksheet();
+    let sheet = worksheet.to_html(false);
+    let key = worksheet.to_html(true);
+    assert_eq!(problems(&sheet).len(), worksheet.set.equations.len());
+    assert_eq!(problems(&key).len(), worksheet.set.equations.len());
+
+    for ((blank, answered), eq) in problems(&sheet)
+        .into_iter()
+        .zip(problems(&key))
+        .zip(&worksheet.set.equations)
+    {
+        let written = eq.to_string();
+        assert_eq!(blank, format!("{written} = <span class=\"blank\"></span>"));
+        assert_eq!(answered, format!("{written} = <b>{}</b>", eq.answer));
+    }
+    assert!(key.contains("Answer key"));
+    assert!(!sheet.contains("Answer key"));
+}
+
+#[test]
+fn equations_use_the_sheets_parentheses() {
+    let mut worksheet = worksheet();
+    worksheet.parentheses = Parentheses::Needed;
+    let sheet = worksheet.to_html(false);
+    for (problem, eq) in problems(&sheet).into_iter().zip(&worksheet.set.equations) {
+        assert!(problem.starts_with(&eq.written(Parentheses::Needed)));
+    }
+}
+
+#[test]
+fn text_is_escaped() {
+    let mut worksheet = worksheet();
+    worksheet.title = "Tom & Jerry's <b>\"sums\"</b>".to_string();
+    let html = worksheet.to_html(true);
+    assert!(html.contains(
+        "<title>Tom &amp; Jerry's &lt;b&gt;&quot;sums&quot;&lt;/b&gt; - Answer key</title>"
+    ));
+    assert!(!html.contains("<b>\"sums\"</b>"));
+}
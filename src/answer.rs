@@ -0,0 +1,160 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::banners::Board;
+use crate::level::{ActiveEquation, AnswerSubmitted, AskedAt, EquationLevel};
+use crate::mode::GameMode;
+use crate::settings::{InputMethod, Settings};
+use crate::solution::{SolutionShown, SolutionSteps};
+
+const MAX_ANSWER_LEN: usize = 5;
+
+/// The equation being answered and the board the answer is typed onto
+#[derive(SystemParam)]
+pub struct Answering<'w, 's> {
+    active: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static ActiveEquation,
+            Option<&'static mut AskedAt>,
+            Has<SolutionShown>,
+        ),
+    >,
+    boards: Query<'w, 's, &'static mut Board, Without<SolutionSteps>>,
+}
+
+/// What the player hears and the rest of the game is told as they answer
+#[derive(SystemParam)]
+pub struct AnswerFeedback<'w> {
+    submitted: EventWriter<'w, AnswerSubmitted>,
+    sfx: EventWriter<'w, PlaySfx>,
+}
+
+/// Lets the player type an answer onto the board and submit it with enter
+pub fn answer_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut level: ResMut<EquationLevel>,
+    settings: Res<Settings>,
+    Answering {
+        mut active,
+        mut boards,
+    }: Answering,
+    AnswerFeedback {
+        mut submitted,
+        mut sfx,
+    }: AnswerFeedback,
+    mut commands: Commands,
+) {
+    let (Ok(mut board), Ok((active_entity, active, mut asked, solution_shown))) =
+        (boards.single_mut(), active.single_mut())
+    else {
+        return;
+    };
+    if !level.in_progress(active.0) {
+        return;
+    }
+    let Some(equation) = level.equation(active.0) else {
+        return;
+    };
+
+    for key in keys.get_just_pressed() {
+        match key {
+            KeyCode::Backspace => {
+                if board.answer.pop().is_some() {
+                    sfx.write(PlaySfx(Sfx::Click));
+                }
+            }
+            KeyCode::Minus | KeyCode::NumpadSubtract if board.answer.is_empty() => {
+                board.answer.push('-');
+                sfx.write(PlaySfx(Sfx::Click));
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let Ok(given) = board.answer.parse::<i16>() else {
+                    continue;
+                };
+                let correct = given == equation.answer;
+                debug!("answered {given} to {equation}, correct: {correct}");
+                let now = time.elapsed_secs();
+                let response_secs = asked.as_ref().map_or(0.0, |asked| now - asked.0);
+                submitted.write(AnswerSubmitted {
+                    index: active.0,
+                    correct,
+                    given,
+                    expected: equation.answer,
+                    difficulty: equation.difficulty(),
+                    response_secs,
+                    first_attempt: level.first_attempt(active.0),
+                    solution_shown,
+                });
+                level.mark_answered(active.0);
+                // time the next attempt from here
+                if let Some(asked) = asked.as_mut() {
+                    asked.0 = now;
+                }
+
+                board.answer.clear();
+                if correct {
+                    let next = active.0 + 1;
+                    board.text = match level.equation(next) {
+                        Some(eq) => eq.written(settings.difficulty.parentheses()),
+                        None => "Level complete!".to_string(),
+                    };
+                    commands.entity(active_entity).despawn();
+                    commands.spawn((ActiveEquation(next), StateScoped(GameMode::InGame)));
+                } else {
+                    level.lives = level.lives.saturating_sub(1);
+                    if level.lives == 0 {
+                        board.text = "Out of lives!".to_string();
+                    }
+                }
+                break;
+            }
+            _ => {
+                if let Some(digit) = digit(key, settings.input)
+                    && board.answer.len() < MAX_ANSWER_LEN
+                {
+                    board.answer.push(digit);
+                    sfx.write(PlaySfx(Sfx::Click));
+                }
+            }
+        }
+    }
+}
+
+fn digit(key: &KeyCode, input: InputMethod) -> Option<char> {
+    let row = match key {
+        KeyCode::Digit0 => Some('0'),
+        KeyCode::Digit1 => Some('1'),
+        KeyCode::Digit2 => Some('2'),
+        KeyCode::Digit3 => Some('3'),
+        KeyCode::Digit4 => Some('4'),
+        KeyCode::Digit5 => Some('5'),
+        KeyCode::Digit6 => Some('6'),
+        KeyCode::Digit7 => Some('7'),
+        KeyCode::Digit8 => Some('8'),
+        KeyCode::Digit9 => Some('9'),
+        _ => None,
+    };
+    let numpad = match key {
+        KeyCode::Numpad0 => Some('0'),
+        KeyCode::Numpad1 => Some('1'),
+        KeyCode::Numpad2 => Some('2'),
+        KeyCode::Numpad3 => Some('3'),
+        KeyCode::Numpad4 => Some('4'),
+        KeyCode::Numpad5 => Some('5'),
+        KeyCode::Numpad6 => Some('6'),
+        KeyCode::Numpad7 => Some('7'),
+        KeyCode::Numpad8 => Some('8'),
+        KeyCode::Numpad9 => Some('9'),
+        _ => None,
+    };
+    match input {
+        InputMethod::Any => row.or(numpad),
+        InputMethod::NumberRow => row,
+        InputMethod::Numpad => numpad,
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::audio::{AudioSinkPlayback, Volume};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::level::{AnswerSubmitted, LevelCompleted};
+use crate::menu::Menu;
+use crate::mode::GameMode;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioVolumes>();
+        app.add_event::<PlaySfx>();
+        app.add_systems(Startup, init_assets);
+        app.add_systems(
+            Update,
+            (
+                toggle_mute,
+                (answer_sfx, play_sfx).chain(),
+                play_music.run_if(state_changed::<GameMode>),
+                apply_volumes.run_if(resource_changed::<AudioVolumes>),
+            ),
+        );
+    }
+}
+
+/// Volume of each bus from 0 to 1. The music and sfx buses are scaled by the master volume.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioVolumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioVolumes {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.5,
+            sfx: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioVolumes {
+    fn bus(&self, bus: f32) -> Volume {
+        if self.muted {
+            Volume::SILENT
+        } else {
+            Volume::Linear(self.master * bus)
+        }
+    }
+
+    pub fn music(&self) -> Volume {
+        self.bus(self.music)
+    }
+
+    pub fn sfx(&self) -> Volume {
+        self.bus(self.sfx)
+    }
+}
+
+/// A one-shot sound effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sfx {
+    Correct,
+    Wrong,
+    Coin,
+    ChestOpen,
+    Click,
+}
+
+/// Plays a sound effect on the sfx bus
+#[derive(Event, Debug)]
+pub struct PlaySfx(pub Sfx);
+
+#[derive(Resource)]
+struct SoundAssets {
+    menu_music: Handle<AudioSource>,
+    game_music: Handle<AudioSource>,
+    correct: Handle<AudioSource>,
+    wrong: Handle<AudioSource>,
+    coin: Handle<AudioSource>,
+    chest_open: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+}
+
+impl SoundAssets {
+    fn sfx(&self, sfx: Sfx) -> Handle<AudioSource> {
+        match sfx {
+            Sfx::Correct => self.correct.clone(),
+            Sfx::Wrong => self.wrong.clone(),
+            Sfx::Coin => self.coin.clone(),
+            Sfx::ChestOpen => self.chest_open.clone(),
+            Sfx::Click => self.click.clone(),
+        }
+    }
+
+    fn music(&self, mode: &GameMode) -> Option<Handle<AudioSource>> {
+        match mode {
+            GameMode::Startup => None,
+            GameMode::StartMenu | GameMode::Settings | GameMode::Profiles | GameMode::Stats => {
+                Some(self.menu_music.clone())
+            }
+            GameMode::InGame => Some(self.game_music.clone()),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Music;
+
+#[derive(Component)]
+struct SoundEffect;
+
+fn init_assets(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(SoundAssets {
+        menu_music: asset_server.load("audio/menu-music.ogg"),
+        game_music: asset_server.load("audio/game-music.ogg"),
+        correct: asset_server.load("audio/correct.ogg"),
+        wrong: asset_server.load("audio/wrong.ogg"),
+        coin: asset_server.load("audio/coin.ogg"),
+        chest_open: asset_server.load("audio/chest-open.ogg"),
+        click: asset_server.load("audio/click.ogg"),
+    });
+}
+
+fn toggle_mute(
+    keys: Res<ButtonInput<KeyCode>>,
+    menus: Query<&Menu>,
+    mut volumes: ResMut<AudioVolumes>,
+) {
+    // M is just a letter while a name is being typed
+    if menus.iter().any(|menu| menu.locked) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyM) {
+        volumes.muted = !volumes.muted;
+        debug!("audio muted: {}", volumes.muted);
+    }
+}
+
+fn answer_sfx(
+    mut submitted: EventReader<AnswerSubmitted>,
+    mut completed: EventReader<LevelCompleted>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    for e in submitted.read() {
+        sfx.write(PlaySfx(if e.correct { Sfx::Correct } else { Sfx::Wrong }));
+    }
+    for _ in completed.read() {
+        sfx.write(PlaySfx(Sfx::ChestOpen));
+    }
+}
+
+fn play_sfx(
+    mut events: EventReader<PlaySfx>,
+    sounds: Res<SoundAssets>,
+    volumes: Res<AudioVolumes>,
+    mut commands: Commands,
+) {
+    for PlaySfx(sfx) in events.read() {
+        commands.spawn((
+            SoundEffect,
+            AudioPlayer::new(sounds.sfx(*sfx)),
+            PlaybackSettings::DESPAWN.with_volume(volumes.sfx()),
+        ));
+    }
+}
+
+/// Swaps to the music for the new game mode
+fn play_music(
+    mode: Res<State<GameMode>>,
+    sounds: Res<SoundAssets>,
+    volumes: Res<AudioVolumes>,
+    music: Query<Entity, With<Music>>,
+    mut commands: Commands,
+) {
+    for entity in music.iter() {
+        commands.entity(entity).despawn();
+    }
+    if let Some(track) = sounds.music(mode.get()) {
+        commands.spawn((
+            Music,
+            AudioPlayer::new(track),
+            PlaybackSettings::LOOP.with_volume(volumes.music()),
+        ));
+    }
+}
+
+/// Applies volume changes to everything already playing
+fn apply_volumes(
+    volumes: Res<AudioVolumes>,
+    mut music: Query<&mut AudioSink, (With<Music>, Without<SoundEffect>)>,
+    mut effects: Query<&mut AudioSink, (With<SoundEffect>, Without<Music>)>,
+) {
+    for mut sink in music.iter_mut() {
+        sink.set_volume(volumes.music());
+    }
+    for mut sink in effects.iter_mut() {
+        sink.set_volume(volumes.sfx());
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+use super::terrain::TileGrid;
+use super::theme::TilesetDescriptor;
+
+/// Width and height of a chunk, in tiles
+pub const CHUNK_SIZE: usize = 16;
+
+/// Builds one mesh for all the tiles in a grid, ground first and decorations on top.
+/// Tiles are centered on their position like sprites are.
+pub fn build_mesh(grid: &TileGrid, descriptor: &TilesetDescriptor) -> Mesh {
+    let tile_size = descriptor.tile_size as f32;
+    let uv_size = Vec2::new(
+        1.0 / descriptor.columns as f32,
+        1.0 / descriptor.rows as f32,
+    );
+    let quads = grid.width * grid.height + grid.decorations.len();
+
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(quads * 4);
+    let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(quads * 4);
+    let mut indices: Vec<u32> = Vec::with_capacity(quads * 6);
+
+    let mut quad = |x: usize, y: usize, index: usize| {
+        let min = Vec2::new(x as f32, y as f32) * tile_size - tile_size / 2.0;
+        let max = min + tile_size;
+        let column = (index % descriptor.columns as usize) as f32;
+        let row = (index / descriptor.columns as usize) as f32;
+        // the top of the image is v = 0
+        let uv_min = Vec2::new(column, row) * uv_size;
+        let uv_max = uv_min + uv_size;
+
+        let start = positions.len() as u32;
+        positions.extend([
+            [min.x, min.y, 0.0],
+            [max.x, min.y, 0.0],
+            [max.x, max.y, 0.0],
+            [min.x, max.y, 0.0],
+        ]);
+        uvs.extend([
+            [uv_min.x, uv_max.y],
+            [uv_max.x, uv_max.y],
+            [uv_max.x, uv_min.y],
+            [uv_min.x, uv_min.y],
+        ]);
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    };
+
+    for x in 0..grid.width {
+        for y in 0..grid.height {
+            quad(x, y, grid.ground_at(x, y));
+        }
+    }
+    // later layers are drawn on top
+    let mut decorations = grid.decorations.iter().collect::<Vec<_>>();
+    decorations.sort_by_key(|d| d.layer);
+    for decoration in decorations {
+        quad(decoration.x, decoration.y, decoration.index);
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
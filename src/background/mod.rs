@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+
+use crate::camera::VirtualCanvas;
+use crate::seed::RngSeed;
+pub use bevy::prelude::*;
+use bevy::sprite::AlphaMode2d;
+
+mod chunk;
+mod terrain;
+mod theme;
+
+pub use chunk::*;
+pub use terrain::*;
+pub use theme::*;
+
+pub struct BackgroundTiles;
+
+impl Plugin for BackgroundTiles {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundTheme>();
+        app.add_systems(
+            Update,
+            (
+                on_theme_change.run_if(resource_changed::<BackgroundTheme>),
+                rebuild.run_if(
+                    resource_exists::<BackgroundTileset>.and(
+                        resource_changed::<BackgroundTileset>
+                            .or(resource_changed::<RngSeed>)
+                            .or(exclusions_changed),
+                    ),
+                ),
+                on_canvas_resize.run_if(
+                    resource_exists::<BackgroundTileset>.and(resource_changed::<VirtualCanvas>),
+                ),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn on_theme_change(
+    theme: Res<BackgroundTheme>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    let descriptor = theme.tileset();
+    let tileset = BackgroundTileset {
+        theme: *theme,
+        material: materials.add(ColorMaterial {
+            texture: Some(asset_server.load(descriptor.image)),
+            alpha_mode: AlphaMode2d::Blend,
+            ..Default::default()
+        }),
+    };
+    debug!("using {:?} background theme", tileset.theme);
+    commands.insert_resource(tileset);
+}
+
+fn exclusions_changed(
+    exclusions: Query<(), Changed<BackgroundExclusion>>,
+    mut removed: RemovedComponents<BackgroundExclusion>,
+) -> bool {
+    // read every removal so they're not seen again next frame
+    let removed = removed.read().count() > 0;
+    !exclusions.is_empty() || removed
+}
+
+/// Regenerates every chunk when the theme or seed changes, and otherwise only the chunks that
+/// an exclusion has moved into, out of or within
+fn rebuild(
+    tileset: Res<BackgroundTileset>,
+    canvas: Res<VirtualCanvas>,
+    seed: Res<RngSeed>,
+    chunks: Query<(Entity, &BackgroundChunk, &ChunkExclusions)>,
+    exclusions: Query<&BackgroundExclusion>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    let descriptor = tileset.theme.tileset();
+    let everything = tileset.is_changed() || seed.is_changed();
+    let exclusions = exclusions.iter().map(|e| e.0).collect::<Vec<_>>();
+    let needed = visible_chunks(canvas.size, descriptor);
+
+    let mut kept = HashSet::new();
+    for (entity, chunk, around) in chunks.iter() {
+        let now = chunk_exclusions(chunk.0, descriptor, &exclusions);
+        let unchanged =
+            around.0.len() == now.len() && now.iter().all(|rect| around.0.contains(rect));
+        if !everything && unchanged && needed.contains(&chunk.0) {
+            kept.insert(chunk.0);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for coord in needed.difference(&kept) {
+        spawn_chunk(
+            *coord,
+            &tileset,
+            seed.0,
+            &exclusions,
+            &mut meshes,
+            &mut commands,
+        );
+    }
+    debug!(
+        "background rebuilt {} of {} chunks",
+        needed.len() - kept.len(),
+        needed.len()
+    );
+}
+
+fn on_canvas_resize(
+    canvas: Res<VirtualCanvas>,
+    tileset: Res<BackgroundTileset>,
+    seed: Res<RngSeed>,
+    chunks: Query<(Entity, &BackgroundChunk)>,
+    exclusions: Query<&BackgroundExclusion>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    let needed = visible_chunks(canvas.size, tileset.theme.tileset());
+
+    let mut existing = HashSet::new();
+    for (entity, chunk) in chunks.iter() {
+        if needed.contains(&chunk.0) {
+            existing.insert(chunk.0);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let exclusions = exclusions.iter().map(|e| e.0).collect::<Vec<_>>();
+    for coord in needed.difference(&existing) {
+        spawn_chunk(
+            *coord,
+            &tileset,
+            seed.0,
+            &exclusions,
+            &mut meshes,
+            &mut commands,
+        );
+    }
+    if needed.len() != existing.len() {
+        debug!(
+            "background resized to {} chunks, {} newly generated",
+            needed.len(),
+            needed.len() - existing.len()
+        );
+    }
+}
+
+/// The chunks covering a canvas of the given size
+fn visible_chunks(size: Vec2, descriptor: &TilesetDescriptor) -> HashSet<UVec2> {
+    let tile_size = descriptor.tile_size as f32;
+    let render_width = (size.x / tile_size) as usize + 1;
+    let render_height = (size.y / tile_size) as usize + 1;
+
+    let mut chunks = HashSet::new();
+    for x in 0..=(render_width / CHUNK_SIZE) {
+        for y in 0..=(render_height / CHUNK_SIZE) {
+            chunks.insert(UVec2::new(x as u32, y as u32));
+        }
+    }
+    chunks
+}
+
+/// The exclusions that overlap a chunk's tiles
+fn chunk_exclusions(
+    coord: UVec2,
+    descriptor: &TilesetDescriptor,
+    exclusions: &[Rect],
+) -> Vec<Rect> {
+    let tile_size = descriptor.tile_size as f32;
+    // tiles are centered on their position
+    let min = (coord * CHUNK_SIZE as u32).as_vec2() * tile_size - tile_size / 2.0;
+    let bounds = Rect::from_corners(min, min + CHUNK_SIZE as f32 * tile_size);
+    exclusions
+        .iter()
+        .filter(|e| !e.intersect(bounds).is_empty())
+        .copied()
+        .collect()
+}
+
+fn spawn_chunk(
+    coord: UVec2,
+    tileset: &BackgroundTileset,
+    seed: u64,
+    exclusions: &[Rect],
+    meshes: &mut Assets<Mesh>,
+    commands: &mut Commands,
+) {
+    let descriptor = tileset.theme.tileset();
+    let tile_size = descriptor.tile_size as f32;
+    let origin = coord * CHUNK_SIZE as u32;
+    let around = chunk_exclusions(coord, descriptor, exclusions);
+
+    let grid = generate(origin, CHUNK_SIZE, CHUNK_SIZE, descriptor, seed, |x, y| {
+        let tile = Rect::from_center_size(
+            Vec2::new(x as f32, y as f32) * tile_size,
+            Vec2::splat(tile_size),
+        );
+        around.iter().any(|e| !e.intersect(tile).is_empty())
+    });
+
+    commands.spawn((
+        BackgroundChunk(coord),
+        ChunkExclusions(around),
+        Mesh2d(meshes.add(build_mesh(&grid, descriptor))),
+        MeshMaterial2d(tileset.material.clone()),
+        Transform::from_translation((origin.as_vec2() * tile_size).extend(0.0)),
+    ));
+}
+
+#[derive(Resource)]
+pub struct BackgroundTileset {
+    pub theme: BackgroundTheme,
+    pub material: Handle<ColorMaterial>,
+}
+
+/// A square of `CHUNK_SIZE` tiles drawn as a single mesh
+#[derive(Component)]
+pub struct BackgroundChunk(pub UVec2);
+
+/// The exclusions a chunk was generated around, to tell when it needs generating again
+#[derive(Component)]
+struct ChunkExclusions(Vec<Rect>);
+
+/// An area of the screen, in world coordinates, that the background keeps clear of terrain and
+/// decorations
+#[derive(Component)]
+pub struct BackgroundExclusion(pub Rect);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::HashMap;
+
+    fn background_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(RngSeed(5));
+        world.insert_resource(BackgroundTileset {
+            theme: BackgroundTheme::Grass,
+            material: Handle::default(),
+        });
+        world
+    }
+
+    fn chunk_entities(world: &mut World) -> HashMap<UVec2, Entity> {
+        world
+            .query::<(Entity, &BackgroundChunk)>()
+            .iter(world)
+            .map(|(entity, chunk)| (chunk.0, entity))
+            .collect()
+    }
+
+    fn resize(world: &mut World, width: f32, height: f32) -> usize {
+        world.insert_resource(VirtualCanvas::new(width, height));
+        world.run_system_once(on_canvas_resize).unwrap();
+        world.query::<&BackgroundChunk>().iter(world).count()
+    }
+
+    #[test]
+    fn chunk_count_stays_bounded_by_the_canvas_size() {
+        let mut world = background_world();
+
+        // a 4k canvas is over 8000 tiles, in 8 by 5 chunks
+        let large = resize(&mut world, 3840.0, 2160.0);
+        assert_eq!(large, 40);
+
+        // shrinking despawns the chunks off screen, growing again only fills them back in
+        assert_eq!(resize(&mut world, 640.0, 360.0), 2);
+        for _ in 0..3 {
+            assert_eq!(resize(&mut world, 3840.0, 2160.0), large);
+            assert_eq!(resize(&mut world, 1920.0, 1080.0), 12);
+        }
+    }
+
+    #[test]
+    fn rebuild_only_regenerates_chunks_around_changed_exclusions() {
+        let mut world = background_world();
+        world.insert_resource(VirtualCanvas::new(1920.0, 1080.0));
+        let board = world
+            .spawn(BackgroundExclusion(Rect::new(100.0, 100.0, 200.0, 150.0)))
+            .id();
+        let rebuild = world.register_system(rebuild);
+        world.run_system(rebuild).unwrap();
+        let mut before = chunk_entities(&mut world);
+        assert_eq!(before.len(), 12);
+
+        let mut regenerated = |world: &mut World| {
+            world.run_system(rebuild).unwrap();
+            let after = chunk_entities(world);
+            let changed = before
+                .iter()
+                .filter(|(coord, entity)| after[*coord] != **entity)
+                .map(|(coord, _)| *coord)
+                .collect::<Vec<_>>();
+            before = after;
+            changed
+        };
+
+        // nothing changed
+        assert!(regenerated(&mut world).is_empty());
+        // moved within the first chunk
+        world
+            .entity_mut(board)
+            .insert(BackgroundExclusion(Rect::new(120.0, 100.0, 220.0, 150.0)));
+        assert_eq!(regenerated(&mut world), [UVec2::ZERO]);
+        // moved into the chunk to the right
+        world
+            .entity_mut(board)
+            .insert(BackgroundExclusion(Rect::new(600.0, 100.0, 700.0, 150.0)));
+        let mut changed = regenerated(&mut world);
+        changed.sort_by_key(|coord| coord.x);
+        assert_eq!(changed, [UVec2::ZERO, UVec2::X]);
+        // removed
+        world.despawn(board);
+        assert_eq!(regenerated(&mut world), [UVec2::X]);
+    }
+}
@@ -0,0 +1,296 @@
+use bevy::math::UVec2;
+use noise::{NoiseFn, Perlin};
+use rand::prelude::{IndexedRandom, IteratorRandom};
+use rand::{Rng, SeedableRng};
+
+use super::theme::{TerrainTiles, TilesetDescriptor, WeightedTiles};
+
+/// The noise field a terrain layer is placed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum NoiseField {
+    // large, smooth features like water and sand
+    Elevation,
+    // smaller patches like different grass types
+    Moisture,
+    // long thin lines that follow the zero crossings of the field
+    Path,
+}
+
+struct TerrainNoise {
+    elevation: Perlin,
+    moisture: Perlin,
+    path: Perlin,
+}
+
+impl TerrainNoise {
+    fn new(seed: u64) -> Self {
+        let seed = (seed ^ (seed >> 32)) as u32;
+        Self {
+            elevation: Perlin::new(seed),
+            moisture: Perlin::new(seed.wrapping_add(1)),
+            path: Perlin::new(seed.wrapping_add(2)),
+        }
+    }
+
+    fn sample(&self, field: NoiseField, x: usize, y: usize) -> f32 {
+        let (noise, scale) = match field {
+            NoiseField::Elevation => (&self.elevation, 0.08),
+            NoiseField::Moisture => (&self.moisture, 0.15),
+            NoiseField::Path => (&self.path, 0.05),
+        };
+        let value = noise.get([x as f64 * scale, y as f64 * scale]) as f32;
+        match field {
+            NoiseField::Path => value.abs(),
+            _ => value,
+        }
+    }
+}
+
+/// The generated atlas indices for a rectangle of background tiles
+pub struct TileGrid {
+    pub width: usize,
+    pub height: usize,
+    // column-major, one entry per tile
+    pub ground: Vec<usize>,
+    pub decorations: Vec<Decoration>,
+}
+
+/// A decoration placed on a tile, relative to the grid origin
+pub struct Decoration {
+    pub x: usize,
+    pub y: usize,
+    pub layer: usize,
+    pub index: usize,
+}
+
+impl TileGrid {
+    pub fn ground_at(&self, x: usize, y: usize) -> usize {
+        self.ground[x * self.height + y]
+    }
+}
+
+/// Generates `width` x `height` tiles starting at the tile `origin`. Tiles for which `excluded`
+/// returns true are kept to plain ground so that UI placed on top of them stays readable.
+///
+/// Terrain only depends on the seed and the tile's position, so grids generated next to each
+/// other line up seamlessly.
+pub fn generate(
+    origin: UVec2,
+    width: usize,
+    height: usize,
+    descriptor: &TilesetDescriptor,
+    seed: u64,
+    excluded: impl Fn(usize, usize) -> bool,
+) -> TileGrid {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(((origin.x as u64) << 32) | origin.y as u64);
+    let noise = TerrainNoise::new(seed);
+    let (origin_x, origin_y) = (origin.x as usize, origin.y as usize);
+
+    // which terrain layer each tile belongs to, None for the base ground. Includes a border of
+    // one tile around the grid so that edges can be matched with the neighbouring grids.
+    let terrain_of = |x: usize, y: usize| {
+        if excluded(x, y) {
+            None
+        } else {
+            descriptor
+                .terrain
+                .iter()
+                .position(|t| t.band.contains(&noise.sample(t.field, x, y)))
+        }
+    };
+    let mut terrain: Vec<Option<usize>> = Vec::with_capacity((width + 2) * (height + 2));
+    for x in 0..width + 2 {
+        for y in 0..height + 2 {
+            terrain.push(
+                match ((origin_x + x).checked_sub(1), (origin_y + y).checked_sub(1)) {
+                    (Some(x), Some(y)) => terrain_of(x, y),
+                    _ => None,
+                },
+            );
+        }
+    }
+    // x and y are relative to the grid origin, offset by the border
+    let terrain_at = |x: usize, y: usize| terrain[x * (height + 2) + y];
+
+    // decorations that can still be placed in this grid, per layer
+    let mut remaining: Vec<Vec<usize>> = descriptor
+        .decorations
+        .iter()
+        .map(|layer| layer.tiles.clone().collect())
+        .collect();
+
+    let mut ground = Vec::with_capacity(width * height);
+    let mut decorations = Vec::new();
+
+    for x in 0..width {
+        for y in 0..height {
+            let (bx, by) = (x + 1, y + 1);
+            let (gx, gy) = (origin_x + x, origin_y + y);
+            let layer = terrain_at(bx, by);
+            let index = match layer.map(|i| &descriptor.terrain[i].tiles) {
+                None => pick(descriptor.ground, &mut rng),
+                Some(TerrainTiles::Fill(tiles)) => pick(tiles, &mut rng),
+                Some(TerrainTiles::Autotile(first)) => {
+                    // tiles off the bottom and left of the map count as the same terrain so
+                    // that transitions are only drawn where the terrain actually changes
+                    let same = |nx: usize, ny: usize, off_map: bool| {
+                        off_map || terrain_at(nx, ny) == layer
+                    };
+                    let mut mask = 0;
+                    if same(bx, by + 1, false) {
+                        mask |= 1;
+                    }
+                    if same(bx + 1, by, false) {
+                        mask |= 2;
+                    }
+                    if same(bx, by - 1, gy == 0) {
+                        mask |= 4;
+                    }
+                    if same(bx - 1, by, gx == 0) {
+                        mask |= 8;
+                    }
+                    first + mask
+                }
+            };
+            ground.push(index);
+
+            // decorations only sit on the base ground
+            if layer.is_some() || excluded(gx, gy) {
+                continue;
+            }
+            for (layer_index, layer) in descriptor.decorations.iter().enumerate() {
+                let candidates = &mut remaining[layer_index];
+                if gx < layer.min_column || candidates.is_empty() || !rng.random_bool(layer.chance)
+                {
+                    continue;
+                }
+                let i = rng.random_range(0..candidates.len());
+                let index = if layer.unique {
+                    candidates.swap_remove(i)
+                } else {
+                    candidates[i]
+                };
+                decorations.push(Decoration {
+                    x,
+                    y,
+                    layer: layer_index,
+                    index,
+                });
+                break;
+            }
+        }
+    }
+
+    TileGrid {
+        width,
+        height,
+        ground,
+        decorations,
+    }
+}
+
+fn pick(tiles: &[WeightedTiles], rng: &mut impl Rng) -> usize {
+    match tiles.choose_weighted(rng, |t| t.weight) {
+        Ok(weighted) => weighted.tiles.clone().choose(rng).unwrap_or(0),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::background::{DecorationLayer, TerrainLayer};
+
+    const WATER: usize = 40;
+
+    // plain ground everywhere below the band, water with edges above it
+    fn descriptor(band: std::ops::Range<f32>) -> TilesetDescriptor {
+        let terrain = vec![TerrainLayer {
+            field: NoiseField::Elevation,
+            band,
+            tiles: TerrainTiles::Autotile(WATER),
+        }];
+        TilesetDescriptor {
+            image: "test.png",
+            tile_size: 32,
+            columns: 8,
+            rows: 9,
+            ground: &[WeightedTiles {
+                tiles: 0..1,
+                weight: 1.0,
+            }],
+            terrain: terrain.leak(),
+            decorations: &[],
+        }
+    }
+
+    #[test]
+    fn edges_face_the_tiles_without_terrain() {
+        // water everywhere except the tile in the middle, which is kept clear
+        let everywhere = descriptor(-10.0..10.0);
+        let grid = generate(UVec2::new(4, 4), 3, 3, &everywhere, 1, |x, y| {
+            (x, y) == (5, 5)
+        });
+        let mask = |x, y| grid.ground_at(x, y) as isize - WATER as isize;
+
+        assert_eq!(grid.ground_at(1, 1), 0);
+        // north = 1, east = 2, south = 4, west = 8
+        assert_eq!(mask(1, 0), 0b1111 & !1);
+        assert_eq!(mask(0, 1), 0b1111 & !2);
+        assert_eq!(mask(1, 2), 0b1111 & !4);
+        assert_eq!(mask(2, 1), 0b1111 & !8);
+        // diagonal neighbours don't count
+        assert_eq!(mask(0, 0), 0b1111);
+        assert_eq!(mask(2, 2), 0b1111);
+    }
+
+    #[test]
+    fn edges_continue_off_the_bottom_and_left_of_the_map() {
+        let everywhere = descriptor(-10.0..10.0);
+        let grid = generate(UVec2::ZERO, 2, 2, &everywhere, 1, |_, _| false);
+        for x in 0..2 {
+            for y in 0..2 {
+                assert_eq!(grid.ground_at(x, y), WATER + 0b1111);
+            }
+        }
+    }
+
+    #[test]
+    fn unique_decorations_are_placed_once_per_chunk() {
+        let decorations = vec![DecorationLayer {
+            tiles: 32..37,
+            chance: 1.0,
+            unique: true,
+            min_column: 0,
+        }];
+        let scattered = TilesetDescriptor {
+            decorations: decorations.leak(),
+            ..descriptor(10.0..11.0)
+        };
+        for origin in [UVec2::ZERO, UVec2::new(16, 0)] {
+            let grid = generate(origin, 16, 16, &scattered, 3, |_, _| false);
+            let mut placed = grid.decorations.iter().map(|d| d.index).collect::<Vec<_>>();
+            placed.sort();
+            assert_eq!(placed, [32, 33, 34, 35, 36]);
+        }
+    }
+
+    #[test]
+    fn neighbouring_grids_line_up() {
+        let lakes = descriptor(-1.0..0.0);
+        let whole = generate(UVec2::ZERO, 32, 16, &lakes, 7, |_, _| false);
+        let left = generate(UVec2::ZERO, 16, 16, &lakes, 7, |_, _| false);
+        let right = generate(UVec2::new(16, 0), 16, 16, &lakes, 7, |_, _| false);
+        for x in 0..16 {
+            for y in 0..16 {
+                assert_eq!(left.ground_at(x, y), whole.ground_at(x, y));
+                assert_eq!(right.ground_at(x, y), whole.ground_at(x + 16, y));
+            }
+        }
+        // the seed gives both water and ground to compare
+        assert!(whole.ground.iter().any(|&index| index >= WATER));
+        assert!(whole.ground.contains(&0));
+    }
+}
@@ -0,0 +1,332 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::terrain::NoiseField;
+
+/// The visual theme of a level's background
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[allow(unused)]
+pub enum BackgroundTheme {
+    #[default]
+    Grass,
+    Beach,
+    Jungle,
+    Cave,
+    ShipDeck,
+}
+
+impl BackgroundTheme {
+    pub const ALL: [BackgroundTheme; 5] = [
+        Self::Grass,
+        Self::Beach,
+        Self::Jungle,
+        Self::Cave,
+        Self::ShipDeck,
+    ];
+
+    pub fn tileset(&self) -> &'static TilesetDescriptor {
+        match self {
+            Self::Grass => &GRASS,
+            Self::Beach => &BEACH,
+            Self::Jungle => &JUNGLE,
+            Self::Cave => &CAVE,
+            Self::ShipDeck => &SHIP_DECK,
+        }
+    }
+}
+
+/// Describes how a background tileset image is laid out and how to pick tiles from it
+pub struct TilesetDescriptor {
+    pub image: &'static str,
+    pub tile_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    // the base layer, one of these is picked for every tile not covered by terrain
+    pub ground: &'static [WeightedTiles],
+    // patches of terrain placed by noise, the first layer whose band contains the noise wins
+    pub terrain: &'static [TerrainLayer],
+    // sparse layers drawn on top of the ground
+    pub decorations: &'static [DecorationLayer],
+}
+
+/// A range of atlas indices and how likely the range is to be picked relative to its siblings
+pub struct WeightedTiles {
+    pub tiles: Range<usize>,
+    pub weight: f32,
+}
+
+pub struct TerrainLayer {
+    pub field: NoiseField,
+    pub band: Range<f32>,
+    pub tiles: TerrainTiles,
+}
+
+#[allow(unused)]
+pub enum TerrainTiles {
+    // tiles picked at random with no transitions
+    Fill(&'static [WeightedTiles]),
+    // the first index of 16 edge tiles ordered by which neighbours share the terrain,
+    // as a bitmask of north = 1, east = 2, south = 4, west = 8
+    Autotile(usize),
+}
+
+pub struct DecorationLayer {
+    pub tiles: Range<usize>,
+    // chance of a decoration being placed on any given tile
+    pub chance: f64,
+    // each tile in the range is only placed once per chunk, so a wide window can show
+    // one in every chunk
+    pub unique: bool,
+    // keeps the left edge of the screen clear
+    pub min_column: usize,
+}
+
+const GRASS: TilesetDescriptor = TilesetDescriptor {
+    image: "grass-tileset.png",
+    tile_size: 32,
+    columns: 8,
+    rows: 5,
+    ground: &[
+        // plain grass
+        WeightedTiles {
+            tiles: 0..1,
+            weight: 0.75,
+        },
+        // grass texture
+        WeightedTiles {
+            tiles: 0..16,
+            weight: 0.2,
+        },
+    ],
+    terrain: &[
+        // patches of textured grass
+        TerrainLayer {
+            field: NoiseField::Moisture,
+            band: 0.25..1.0,
+            tiles: TerrainTiles::Fill(&[WeightedTiles {
+                tiles: 0..16,
+                weight: 1.0,
+            }]),
+        },
+        // patches of flowers and pebbles
+        TerrainLayer {
+            field: NoiseField::Moisture,
+            band: -1.0..-0.4,
+            tiles: TerrainTiles::Fill(&[
+                WeightedTiles {
+                    tiles: 0..16,
+                    weight: 0.6,
+                },
+                WeightedTiles {
+                    tiles: 16..32,
+                    weight: 0.4,
+                },
+            ]),
+        },
+    ],
+    decorations: &[
+        // rocks
+        DecorationLayer {
+            tiles: 32..37,
+            chance: 0.01,
+            unique: true,
+            min_column: 6,
+        },
+    ],
+};
+
+const BEACH: TilesetDescriptor = TilesetDescriptor {
+    image: "beach-tileset.png",
+    tile_size: 32,
+    columns: 8,
+    rows: 9,
+    ground: &[
+        // smooth sand
+        WeightedTiles {
+            tiles: 0..1,
+            weight: 0.7,
+        },
+        // rippled sand
+        WeightedTiles {
+            tiles: 0..16,
+            weight: 0.25,
+        },
+        // shells and pebbles
+        WeightedTiles {
+            tiles: 16..24,
+            weight: 0.05,
+        },
+    ],
+    terrain: &[
+        // water
+        TerrainLayer {
+            field: NoiseField::Elevation,
+            band: -1.0..-0.25,
+            tiles: TerrainTiles::Autotile(40),
+        },
+        // paths
+        TerrainLayer {
+            field: NoiseField::Path,
+            band: 0.0..0.04,
+            tiles: TerrainTiles::Autotile(56),
+        },
+    ],
+    decorations: &[
+        // starfish and crabs
+        DecorationLayer {
+            tiles: 24..32,
+            chance: 0.02,
+            unique: false,
+            min_column: 6,
+        },
+        // driftwood and rocks
+        DecorationLayer {
+            tiles: 32..38,
+            chance: 0.01,
+            unique: true,
+            min_column: 6,
+        },
+    ],
+};
+
+const JUNGLE: TilesetDescriptor = TilesetDescriptor {
+    image: "jungle-tileset.png",
+    tile_size: 32,
+    columns: 8,
+    rows: 9,
+    ground: &[
+        // dark undergrowth
+        WeightedTiles {
+            tiles: 0..1,
+            weight: 0.6,
+        },
+        // leaf litter
+        WeightedTiles {
+            tiles: 0..16,
+            weight: 0.3,
+        },
+        // roots and mud
+        WeightedTiles {
+            tiles: 16..24,
+            weight: 0.1,
+        },
+    ],
+    terrain: &[
+        // water
+        TerrainLayer {
+            field: NoiseField::Elevation,
+            band: -1.0..-0.3,
+            tiles: TerrainTiles::Autotile(40),
+        },
+        // paths
+        TerrainLayer {
+            field: NoiseField::Path,
+            band: 0.0..0.04,
+            tiles: TerrainTiles::Autotile(56),
+        },
+        // tall grass
+        TerrainLayer {
+            field: NoiseField::Moisture,
+            band: 0.3..1.0,
+            tiles: TerrainTiles::Fill(&[WeightedTiles {
+                tiles: 16..24,
+                weight: 1.0,
+            }]),
+        },
+    ],
+    decorations: &[
+        // ferns
+        DecorationLayer {
+            tiles: 24..32,
+            chance: 0.05,
+            unique: false,
+            min_column: 6,
+        },
+        // fallen logs
+        DecorationLayer {
+            tiles: 32..36,
+            chance: 0.01,
+            unique: true,
+            min_column: 6,
+        },
+    ],
+};
+
+const CAVE: TilesetDescriptor = TilesetDescriptor {
+    image: "cave-tileset.png",
+    tile_size: 32,
+    columns: 8,
+    rows: 7,
+    ground: &[
+        // stone floor
+        WeightedTiles {
+            tiles: 0..1,
+            weight: 0.7,
+        },
+        // cracked stone
+        WeightedTiles {
+            tiles: 0..16,
+            weight: 0.25,
+        },
+        // puddles and moss
+        WeightedTiles {
+            tiles: 16..24,
+            weight: 0.05,
+        },
+    ],
+    terrain: &[
+        // water
+        TerrainLayer {
+            field: NoiseField::Elevation,
+            band: -1.0..-0.35,
+            tiles: TerrainTiles::Autotile(40),
+        },
+    ],
+    decorations: &[
+        // crystals
+        DecorationLayer {
+            tiles: 24..30,
+            chance: 0.01,
+            unique: true,
+            min_column: 6,
+        },
+        // boulders
+        DecorationLayer {
+            tiles: 32..37,
+            chance: 0.015,
+            unique: true,
+            min_column: 6,
+        },
+    ],
+};
+
+const SHIP_DECK: TilesetDescriptor = TilesetDescriptor {
+    image: "ship-deck-tileset.png",
+    tile_size: 32,
+    columns: 8,
+    rows: 5,
+    ground: &[
+        // planks
+        WeightedTiles {
+            tiles: 0..8,
+            weight: 0.85,
+        },
+        // worn and knotted planks
+        WeightedTiles {
+            tiles: 8..16,
+            weight: 0.15,
+        },
+    ],
+    terrain: &[],
+    decorations: &[
+        // ropes, barrels and crates
+        DecorationLayer {
+            tiles: 32..38,
+            chance: 0.01,
+            unique: true,
+            min_column: 6,
+        },
+    ],
+};
@@ -1,141 +1,407 @@
-use bevy::color::palettes::css::BLACK;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
+use bevy::sprite::Anchor;
+use bevy::text::{TextBounds, TextLayoutInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::background::BackgroundExclusion;
+use crate::camera::VirtualCanvas;
+use crate::layout::{Anchored, ScreenAnchor};
+use crate::level::AnswerSubmitted;
+use crate::panel::{NineSlice, Panel, PanelContent, PanelGrid, PanelSize};
+use crate::tween::{Tween, TweenCompleted, Tweens};
+
 pub struct BannersPlugin;
 
 impl Plugin for BannersPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, init_assets);
         app.add_observer(render_board);
+        app.add_systems(
+            Update,
+            (
+                fit_board_text,
+                resize_board_shadow,
+                (on_board_changed, on_board_tween).chain(),
+                flash_board,
+            ),
+        );
     }
 }
 
-fn init_assets(asset_server: Res<AssetServer>) {
-    let _image: Handle<Image> = asset_server.load("board.png");
+fn init_assets(
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut commands: Commands,
+) {
+    let image: Handle<Image> = asset_server.load("board.png");
+    commands.insert_resource(BoardSlice(NineSlice::from_grid(
+        image,
+        UVec2::new(31, 33),
+        &mut texture_atlas_layouts,
+    )));
 }
 
+#[derive(Resource)]
+struct BoardSlice(NineSlice);
+
+// font sizes the board steps down through when shrinking text, multiples of the pixel font size
+const BOARD_FONT_SIZES: [f32; 3] = [32.0, 24.0, 16.0];
+const BOARD_MARGIN: f32 = 16.0;
+const BOARD_PADDING: Vec2 = Vec2::new(24.0, 16.0);
+
+// each half of a transition, out and then back in
+const TRANSITION_SECS: f32 = 0.3;
+const FLASH_SECS: f32 = 0.5;
+const BOARD_OUT: &str = "board_out";
+const BOARD_IN: &str = "board_in";
+
 #[derive(Component, Debug)]
 pub struct Board {
     pub text: String,
+    // what the player has typed so far
+    pub answer: String,
+    // leaves the answer off the end of the text, eg. while showing how to solve the question
+    pub answer_hidden: bool,
+    pub overflow: BoardOverflow,
+    pub transition: BoardTransition,
+}
+
+/// What the board does when its text is wider than the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BoardOverflow {
+    // wrap onto more lines at the full font size
+    #[default]
+    Wrap,
+    // step down the font size first, then wrap once at the smallest size
+    Shrink,
+}
+
+/// How the board animates when its text changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(unused)]
+pub enum BoardTransition {
+    // slides off the top of the canvas and back in with the new text
+    #[default]
+    Slide,
+    // folds flat and opens back up with the new text
+    Flip,
 }
 
 impl Board {
     pub fn new(text: String) -> Self {
-        Self { text }
+        Self {
+            text,
+            answer: String::new(),
+            answer_hidden: false,
+            overflow: BoardOverflow::default(),
+            transition: BoardTransition::default(),
+        }
     }
 }
 
+/// The entities that make up a rendered board
+#[derive(Component)]
+struct BoardParts {
+    text: Entity,
+    answer: Entity,
+}
+
+/// The question text currently on the board, which lags behind `Board::text` during a transition
+#[derive(Component)]
+struct BoardShown(String);
+
+#[derive(Component)]
+struct Transitioning;
+
+/// The assets a board is drawn with
+#[derive(SystemParam)]
+struct BoardAssets<'w> {
+    slice: Res<'w, BoardSlice>,
+    asset_server: Res<'w, AssetServer>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+}
+
 fn render_board(
     trigger: Trigger<OnAdd, Board>,
     boards: Query<&Board>,
-    windows: Query<&Window>,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    canvas: Res<VirtualCanvas>,
+    mut assets: BoardAssets,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     let board = boards.get(trigger.target()).unwrap();
-    let image: Handle<Image> = asset_server.load("board.png");
-    let layout = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-        UVec2::new(31, 33),
-        3,
-        3,
-        None,
-        None,
-    ));
 
     debug!(
         "board image loaded? {:?}",
-        asset_server.dependency_load_state(&image)
+        assets
+            .asset_server
+            .dependency_load_state(&assets.slice.0.image)
     );
 
-    let win_size = match windows.single() {
-        Ok(win) => (win.width(), win.height()),
-        _ => (0.0, 0.0), // what to do here?
+    let grayish_blue = Color::srgb(51.0 / 255.0, 50.0 / 255.0, 61.0 / 255.0);
+    let panel = Panel::new(
+        assets.slice.0.clone(),
+        PanelSize::Content {
+            padding: BOARD_PADDING,
+            min_tiles: UVec2::new(4, 2),
+        },
+    );
+    // sized to the text once it has been measured
+    let anchored = Anchored::new(
+        ScreenAnchor::TopCenter,
+        Vec2::new(0.0, BOARD_MARGIN),
+        panel.tiles(None).as_vec2() * assets.slice.0.tile_size,
+    );
+    let bounds = anchored.rect(canvas.size);
+    let font = TextFont {
+        font: assets.asset_server.load("monogram-extended.ttf"),
+        font_size: BOARD_FONT_SIZES[0],
+        font_smoothing: bevy::text::FontSmoothing::None,
+        ..Default::default()
     };
 
-    let tile_width = 31.0;
-    let tile_height = 33.0;
-    let win_width = win_size.0 / 3.0;
-    let win_height = win_size.0 / 3.0;
-
-    let x_pos_start = win_width / 2.0 - (tile_width * 3.0);
-    let y_pos_start = (win_height / 2.0) - (tile_height * 0.75);
-    let banner_width = tile_width * 8.0;
-    let banner_height = tile_width * 2.0;
-    let grayish_blue = Color::srgb(51.0 / 255.0, 50.0 / 255.0, 61.0 / 255.0);
+    let answer = commands
+        .spawn((
+            TextSpan::new(answer_text(board)),
+            font.clone(),
+            TextColor(grayish_blue),
+        ))
+        .id();
+    let text = commands
+        .spawn((
+            font,
+            Text2d::new(board.text.clone()),
+            TextColor(grayish_blue),
+            Anchor::CenterLeft,
+            PanelContent,
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ))
+        .add_child(answer)
+        .id();
 
     commands
         .entity(trigger.target())
         .insert((
-            Transform::from_xyz(x_pos_start, y_pos_start, 5.0),
+            Transform::from_translation(bounds.min.extend(5.0)),
             Visibility::Visible,
+            BackgroundExclusion(bounds),
+            anchored,
+            panel,
+            BoardParts { text, answer },
+            BoardShown(board.text.clone()),
         ))
+        .add_child(text)
         .with_child((
-            TextFont {
-                font: asset_server.load("monogram-extended.ttf"),
-                font_size: 32.0,
-                font_smoothing: bevy::text::FontSmoothing::None,
-                ..Default::default()
-            },
-            Text2d::new(board.text.clone()),
-            TextColor(grayish_blue),
-            Transform::from_xyz(banner_width * 0.45, tile_height * 0.55, 0.0),
-        ))
-        .with_child((
-            Mesh2d(meshes.add(Rectangle::new(banner_width, banner_height))),
-            MeshMaterial2d(materials.add(grayish_blue.with_alpha(0.33))),
-            Transform::from_xyz(banner_width * 0.47, 6.0, -1.0),
+            // sized to the panel once it has been drawn
+            BoardShadow,
+            Mesh2d(assets.meshes.add(Rectangle::new(1.0, 1.0))),
+            MeshMaterial2d(assets.materials.add(grayish_blue.with_alpha(0.33))),
+            Transform::from_xyz(0.0, 0.0, -1.0),
         ));
 
-    for x in 0..8 {
-        let x_pos = x as f32 * tile_width;
-        for y in 0..2 {
-            let y_pos = y as f32 * tile_height;
-            let index = if x == 0 && y == 0 {
-                BoardTiles::BottomLeft
-            } else if x == 0 && y == 1 {
-                BoardTiles::TopLeft
-            } else if x == 7 && y == 0 {
-                BoardTiles::BottomRight
-            } else if x == 7 && y == 1 {
-                BoardTiles::TopRight
-            } else if y == 0 {
-                BoardTiles::Bottom
-            } else if y == 1 {
-                BoardTiles::Top
-            } else {
-                BoardTiles::Middle
-            };
-
-            commands.entity(trigger.target()).with_child((
-                Sprite {
-                    image: image.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: layout.clone(),
-                        index: index as usize,
-                    }),
-                    ..Default::default()
-                },
-                Transform::from_xyz(x_pos, y_pos, 0.0),
-            ));
+    // TODO: rename "board" to "box banner"
+}
+
+#[derive(Component)]
+struct BoardShadow;
+
+/// Keeps long equations on the canvas by wrapping or shrinking the board's text
+fn fit_board_text(
+    canvas: Res<VirtualCanvas>,
+    boards: Query<&Board>,
+    mut texts: Query<(
+        Ref<TextLayoutInfo>,
+        &mut TextFont,
+        &mut TextBounds,
+        &ChildOf,
+    )>,
+) {
+    let max_width = canvas.width() - BOARD_MARGIN * 2.0 - BOARD_PADDING.x * 2.0;
+
+    for (info, mut font, mut bounds, parent) in texts.iter_mut() {
+        let Ok(board) = boards.get(parent.parent()) else {
+            continue;
+        };
+        if !info.is_changed() || info.size.x <= max_width {
+            continue;
+        }
+
+        let smaller = BOARD_FONT_SIZES
+            .iter()
+            .find(|size| **size < font.font_size)
+            .copied();
+        match (board.overflow, smaller) {
+            (BoardOverflow::Shrink, Some(size)) => {
+                debug!("shrinking board text to {size}");
+                font.font_size = size;
+            }
+            _ if bounds.width.is_none() => {
+                debug!("wrapping board text to {max_width}");
+                bounds.width = Some(max_width);
+            }
+            _ => {}
         }
     }
+}
 
-    // TODO: rename "board" to "box banner"
+/// Stretches the drop shadow to match the size of the board's panel
+fn resize_board_shadow(
+    boards: Query<(Ref<PanelGrid>, &Panel), With<Board>>,
+    mut shadows: Query<(&mut Transform, &ChildOf), With<BoardShadow>>,
+) {
+    for (mut transform, parent) in shadows.iter_mut() {
+        let Ok((grid, panel)) = boards.get(parent.parent()) else {
+            continue;
+        };
+        if !grid.is_changed() {
+            continue;
+        }
+        let size = grid.0.as_vec2() * panel.slice.tile_size;
+        // offset down and to the right, slightly shorter than the board
+        transform.translation.x = size.x / 2.0 + 8.0;
+        transform.translation.y = size.y / 2.0 - 10.5;
+        transform.scale = Vec3::new(size.x, size.y - 4.0, 1.0);
+    }
+}
+
+fn answer_text(board: &Board) -> String {
+    if board.answer_hidden {
+        String::new()
+    } else if board.answer.is_empty() {
+        " = ?".to_string()
+    } else {
+        format!(" = {}", board.answer)
+    }
+}
+
+type BoardItem<'a> = (
+    Entity,
+    Ref<'a, Board>,
+    &'a BoardShown,
+    &'a BoardParts,
+    &'a Anchored,
+    Has<Transitioning>,
+);
+
+fn on_board_changed(
+    canvas: Res<VirtualCanvas>,
+    boards: Query<BoardItem>,
+    mut spans: Query<&mut TextSpan>,
+    mut commands: Commands,
+) {
+    for (entity, board, shown, parts, anchored, transitioning) in boards.iter() {
+        if !board.is_changed() {
+            continue;
+        }
+        if let Ok(mut span) = spans.get_mut(parts.answer) {
+            let text = answer_text(&board);
+            if span.0 != text {
+                span.0 = text;
+            }
+        }
+        if board.text == shown.0 || transitioning {
+            continue;
+        }
+
+        let rect = anchored.rect(canvas.size);
+        let home = rect.min.extend(5.0);
+        let tweens = match board.transition {
+            BoardTransition::Slide => {
+                let away = home.with_y(canvas.height());
+                Tweens::new()
+                    .then(
+                        Tween::translation(home, away, TRANSITION_SECS, EaseFunction::CubicIn)
+                            .with_label(BOARD_OUT),
+                    )
+                    .then(
+                        Tween::translation(away, home, TRANSITION_SECS, EaseFunction::CubicOut)
+                            .with_label(BOARD_IN),
+                    )
+            }
+            BoardTransition::Flip => {
+                // fold around the middle of the board
+                let folded = Vec3::new(1.0, 0.0, 1.0);
+                let middle = home + Vec3::Y * rect.height() / 2.0;
+                Tweens::new()
+                    .then_parallel([
+                        Tween::scale(Vec3::ONE, folded, TRANSITION_SECS, EaseFunction::CubicIn)
+                            .with_label(BOARD_OUT),
+                        Tween::translation(home, middle, TRANSITION_SECS, EaseFunction::CubicIn),
+                    ])
+                    .then_parallel([
+                        Tween::scale(folded, Vec3::ONE, TRANSITION_SECS, EaseFunction::CubicOut)
+                            .with_label(BOARD_IN),
+                        Tween::translation(middle, home, TRANSITION_SECS, EaseFunction::CubicOut),
+                    ])
+            }
+        };
+        commands.entity(entity).insert((Transitioning, tweens));
+    }
 }
 
-#[derive(Debug)]
-#[repr(u16)]
-enum BoardTiles {
-    TopLeft = 0,
-    Top = 1,
-    TopRight = 2,
-    Left = 3,
-    Middle = 4,
-    Right = 5,
-    BottomLeft = 6,
-    Bottom = 7,
-    BottomRight = 8,
+/// Swaps in the new question while the board is out of view
+fn on_board_tween(
+    mut completed: EventReader<TweenCompleted>,
+    mut boards: Query<(&mut Board, &BoardParts, &mut BoardShown)>,
+    mut texts: Query<(&mut Text2d, &mut TextFont, &mut TextBounds)>,
+    mut spans: Query<&mut TextSpan>,
+    mut commands: Commands,
+) {
+    for e in completed.read() {
+        let Ok((mut board, parts, mut shown)) = boards.get_mut(e.entity) else {
+            continue;
+        };
+        match e.label {
+            BOARD_OUT => {
+                shown.0 = board.text.clone();
+                if let Ok((mut text, mut font, mut bounds)) = texts.get_mut(parts.text) {
+                    text.0 = board.text.clone();
+                    // start over at full size, the new text is fitted again once measured
+                    font.font_size = BOARD_FONT_SIZES[0];
+                    bounds.width = None;
+                }
+                if let Ok(mut span) = spans.get_mut(parts.answer) {
+                    span.0 = answer_text(&board);
+                }
+            }
+            BOARD_IN => {
+                commands.entity(e.entity).remove::<Transitioning>();
+                if board.text != shown.0 {
+                    // the question moved on again mid-transition
+                    board.set_changed();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tints the board's tiles green or red and fades back to their normal color
+fn flash_board(
+    mut submitted: EventReader<AnswerSubmitted>,
+    boards: Query<&Children, With<Board>>,
+    sprites: Query<(), With<Sprite>>,
+    mut commands: Commands,
+) {
+    for e in submitted.read() {
+        let color = if e.correct {
+            Color::srgb(0.55, 0.95, 0.55)
+        } else {
+            Color::srgb(1.0, 0.45, 0.45)
+        };
+        for child in boards.iter().flatten() {
+            if sprites.contains(*child) {
+                commands
+                    .entity(*child)
+                    .insert(Tweens::new().then(Tween::color(
+                        color,
+                        Color::WHITE,
+                        FLASH_SECS,
+                        EaseFunction::Linear,
+                    )));
+            }
+        }
+    }
 }
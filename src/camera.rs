@@ -1,25 +1,114 @@
 use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
 
 pub struct CameraSetup;
 
 impl Plugin for CameraSetup {
     fn build(&self, app: &mut App) {
+        app.insert_resource(VirtualCanvas::new(640.0, 360.0));
         app.add_systems(Startup, camera);
+        app.add_systems(Update, fit_canvas);
     }
 }
 
 #[derive(Component)]
 pub struct MainCamera;
 
-fn camera(mut commands: Commands) {
+/// The fixed logical canvas that all layout is done against. The canvas is drawn to the window
+/// at the largest whole number scale that fits, and the rest of the window is letterboxed. A
+/// window too small for the canvas shrinks it to fit, keeping its aspect ratio.
+/// World coordinates run from (0, 0) at the bottom left of the canvas to `size` at the top right.
+#[derive(Resource, Debug)]
+pub struct VirtualCanvas {
+    pub size: Vec2,
+    // how many physical pixels make up one logical pixel
+    pub scale: u32,
+}
+
+impl VirtualCanvas {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            size: Vec2::new(width, height),
+            scale: 1,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.size.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.size.y
+    }
+}
+
+fn camera(mut commands: Commands, canvas: Res<VirtualCanvas>) {
     commands.spawn((
         Msaa::Off,
         Camera2d,
         MainCamera,
         Projection::from(OrthographicProjection {
-            scale: 1.0 / 3.0,
+            scaling_mode: ScalingMode::Fixed {
+                width: canvas.width(),
+                height: canvas.height(),
+            },
             viewport_origin: Vec2::new(0.0, 0.0),
             ..OrthographicProjection::default_2d()
         }),
     ));
+
+    // clears the letterbox bars around the canvas, nothing is drawn to this layer
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: -1,
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..Default::default()
+        },
+        RenderLayers::layer(1),
+    ));
+}
+
+fn fit_canvas(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut Camera, With<MainCamera>>,
+    mut canvas: ResMut<VirtualCanvas>,
+) {
+    let (Ok(window), Ok(mut camera)) = (windows.single(), cameras.single_mut()) else {
+        return;
+    };
+
+    let physical = window.physical_size();
+    if physical.x == 0 || physical.y == 0 {
+        // minimized
+        return;
+    }
+    let canvas_size = canvas.size.as_uvec2();
+    let scale = (physical.x / canvas_size.x).min(physical.y / canvas_size.y);
+    let viewport_size = if scale > 0 {
+        canvas_size * scale
+    } else {
+        // a window smaller than the canvas can't stay pixel perfect, but it keeps its shape
+        let fit = (physical.as_vec2() / canvas.size).min_element();
+        (canvas.size * fit).as_uvec2().max(UVec2::ONE)
+    };
+    let scale = scale.max(1);
+
+    let physical_position = (physical - viewport_size) / 2;
+    let unchanged = camera.viewport.as_ref().is_some_and(|v| {
+        v.physical_position == physical_position && v.physical_size == viewport_size
+    });
+    if !unchanged {
+        camera.viewport = Some(Viewport {
+            physical_position,
+            physical_size: viewport_size,
+            ..Default::default()
+        });
+    }
+    if canvas.scale != scale {
+        debug!("scaling canvas by {scale}");
+        canvas.scale = scale;
+    }
 }
@@ -1,7 +0,0 @@
-mod equation;
-mod operation;
-mod values;
-
-pub use equation::*;
-pub use operation::*;
-pub use values::*;
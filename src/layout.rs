@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+use crate::background::BackgroundExclusion;
+use crate::camera::VirtualCanvas;
+
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, layout_anchored);
+    }
+}
+
+/// Where on the canvas an element is pinned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum ScreenAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Positions an element relative to the canvas whenever it is added, changed or the window is
+/// resized. The element's transform is placed at the bottom left corner of its box, so children
+/// should be laid out from (0, 0) to `size`.
+#[derive(Component, Debug, Clone)]
+pub struct Anchored {
+    pub anchor: ScreenAnchor,
+    // distance from the anchored edges, ignored along centered axes
+    pub margin: Vec2,
+    pub size: Vec2,
+}
+
+impl Anchored {
+    pub fn new(anchor: ScreenAnchor, margin: Vec2, size: Vec2) -> Self {
+        Self {
+            anchor,
+            margin,
+            size,
+        }
+    }
+
+    /// The box the element occupies on a canvas of the given size
+    pub fn rect(&self, canvas: Vec2) -> Rect {
+        use ScreenAnchor::*;
+
+        let x = match self.anchor {
+            TopLeft | CenterLeft | BottomLeft => self.margin.x,
+            TopCenter | Center | BottomCenter => (canvas.x - self.size.x) / 2.0,
+            TopRight | CenterRight | BottomRight => canvas.x - self.size.x - self.margin.x,
+        };
+        let y = match self.anchor {
+            BottomLeft | BottomCenter | BottomRight => self.margin.y,
+            CenterLeft | Center | CenterRight => (canvas.y - self.size.y) / 2.0,
+            TopLeft | TopCenter | TopRight => canvas.y - self.size.y - self.margin.y,
+        };
+
+        // keep to whole pixels so sprites stay crisp
+        let min = Vec2::new(x, y).round();
+        Rect::from_corners(min, min + self.size)
+    }
+}
+
+fn layout_anchored(
+    canvas: Res<VirtualCanvas>,
+    mut resize_reader: EventReader<WindowResized>,
+    mut anchored: Query<(
+        Ref<Anchored>,
+        &mut Transform,
+        Option<&mut BackgroundExclusion>,
+    )>,
+) {
+    let resized = resize_reader.read().count() > 0 || canvas.is_changed();
+
+    for (anchor, mut transform, exclusion) in anchored.iter_mut() {
+        if !resized && !anchor.is_changed() {
+            continue;
+        }
+        let rect = anchor.rect(canvas.size);
+        if transform.translation.truncate() != rect.min {
+            transform.translation = rect.min.extend(transform.translation.z);
+        }
+        if let Some(mut exclusion) = exclusion
+            && exclusion.0 != rect
+        {
+            exclusion.0 = rect;
+        }
+    }
+}
@@ -1,84 +1,314 @@
-use std::collections::HashSet;
-
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use eq_gen::Equation;
 use rand::{SeedableRng, random_range};
 
+use crate::answer::answer_input;
+use crate::background::BackgroundTheme;
 use crate::banners::Board;
-use crate::eq_gen::{Equation, NumberType, OperationConfig, OperationType};
 use crate::mode::GameMode;
+use crate::review::{REVIEW_SESSION_LEN, REVIEWS_PER_LEVEL, ReviewDeck, ReviewSession};
 use crate::seed::RngSeed;
+use crate::settings::{DifficultySettings, Settings};
+use crate::stats::unix_now;
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 pub struct Levels;
 
 impl Plugin for Levels {
     fn build(&self, app: &mut App) {
         app.init_resource::<EquationLevel>();
+        app.init_resource::<Campaign>();
+        app.add_event::<AnswerSubmitted>();
+        app.add_event::<LevelCompleted>();
         app.add_observer(on_equation);
         app.add_systems(OnEnter(GameMode::InGame), setup);
         app.init_state::<GameMode>();
+        app.enable_state_scoped_entities::<GameMode>();
         app.add_systems(Update, start_game.run_if(in_state(GameMode::Startup)));
+        app.add_systems(
+            Update,
+            (leave_level, answer_input)
+                .chain()
+                .run_if(in_state(GameMode::InGame)),
+        );
     }
 }
 
 fn start_game(mut next_state: ResMut<NextState<GameMode>>, time: Res<Time>) {
     if time.elapsed_secs() > 0.25 {
-        debug!("changing game mode to in game");
-        next_state.set(GameMode::InGame);
+        debug!("changing game mode to start menu");
+        next_state.set(GameMode::StartMenu);
     }
 }
 
-fn setup(mut seed: ResMut<RngSeed>, mut level: ResMut<EquationLevel>, mut commands: Commands) {
-    seed.0 = random_range(0..u64::MAX);
-    let mut rng = ChaCha8Rng::seed_from_u64(seed.0);
-    let cfg = OperationConfig {
-        answer_min: 1,
-        answer_max: 20,
-        value_min: -10,
-        value_max: 20,
-        allowed_numerics: HashSet::from([NumberType::Whole, NumberType::Negative]),
-        allowed_operations: HashSet::from([
-            OperationType::Add,
-            OperationType::Subtract,
-            OperationType::Multiply,
-            OperationType::Divide,
-        ]),
+/// Where the next level comes from: a level left part way, a review session or a new level
+/// with any due reviews mixed in
+#[derive(SystemParam)]
+struct NextLevel<'w> {
+    campaign: Res<'w, Campaign>,
+    deck: Res<'w, ReviewDeck>,
+    resume: Option<ResMut<'w, ResumeLevel>>,
+    review: Option<Res<'w, ReviewSession>>,
+}
+
+fn setup(
+    mut seed: ResMut<RngSeed>,
+    mut level: ResMut<EquationLevel>,
+    mut theme: ResMut<BackgroundTheme>,
+    settings: Res<Settings>,
+    NextLevel {
+        campaign,
+        deck,
+        resume,
+        review,
+    }: NextLevel,
+    mut commands: Commands,
+) {
+    let active = match resume {
+        Some(mut resume) => {
+            debug!("resuming level at equation {}", resume.active);
+            *level = std::mem::take(&mut resume.level);
+            commands.remove_resource::<ResumeLevel>();
+            resume.active
+        }
+        None if review.is_some() => {
+            debug!("starting a review session");
+            commands.remove_resource::<ReviewSession>();
+            *level = review_level(
+                campaign.unlocked_themes(),
+                deck.due(unix_now(), REVIEW_SESSION_LEN),
+            );
+            0
+        }
+        None => {
+            *level = new_level(
+                &settings,
+                campaign.unlocked_themes(),
+                deck.due(unix_now(), REVIEWS_PER_LEVEL),
+            );
+            0
+        }
     };
+    seed.0 = level.seed;
+    *theme = level.theme;
+
+    let text = match level.equations.get(active) {
+        Some(eq) => eq.written(settings.difficulty.parentheses()),
+        None => "Level complete!".to_string(),
+    };
+    commands.spawn((ActiveEquation(active), StateScoped(GameMode::InGame)));
+    commands.spawn((
+        Board {
+            overflow: settings.accessibility.board_overflow,
+            ..Board::new(text)
+        },
+        StateScoped(GameMode::InGame),
+    ));
+}
+
+/// A level of freshly generated equations, with `reviews` taking the place of some of them
+fn new_level(
+    settings: &Settings,
+    themes: &[BackgroundTheme],
+    reviews: Vec<Equation>,
+) -> EquationLevel {
+    let seed = random_range(0..u64::MAX);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let theme = *themes.choose(&mut rng).unwrap();
+
+    let mut equations = new_equations(&settings.difficulty, &mut rng);
+    if equations.is_empty() {
+        // nothing fits the settings, so fall back to the easiest rather than an empty level
+        warn!("no equations fit the difficulty, using the easiest instead");
+        equations = new_equations(&DifficultySettings::for_grade(0), &mut rng);
+    }
+    equations.sort();
+    equations.truncate(LEVEL_LEN.saturating_sub(reviews.len()));
+    // reviews fall in among the new equations by difficulty
+    equations.extend(reviews);
+    equations.sort();
 
+    EquationLevel {
+        equations,
+        theme,
+        seed,
+        lives: LIVES,
+        answered: None,
+    }
+}
+
+/// Up to 12 equations at the given difficulty, fewer if some don't fit it
+fn new_equations(difficulty: &DifficultySettings, rng: &mut impl Rng) -> Vec<Equation> {
+    let cfg = difficulty.operation_config();
+    // order of operations needs at least two operations to matter
+    let fewest = if difficulty.order_of_operations { 2 } else { 1 };
     let mut equations: Vec<Equation> = Vec::new();
     for _ in 0..12 {
-        let op_count = (1..=3).choose(&mut rng).unwrap();
-        if let Some(eq) = Equation::rnd_compound(&cfg, op_count, &mut rng) {
+        let op_count = (fewest..=difficulty.max_operations.max(fewest))
+            .choose(rng)
+            .unwrap();
+        let eq = if difficulty.order_of_operations {
+            Equation::rnd_precedence_trap(&cfg, op_count, rng)
+                .ok()
+                .or_else(|| Equation::rnd_compound(&cfg, op_count, rng))
+        } else {
+            Equation::rnd_compound(&cfg, op_count, rng)
+        };
+        if let Some(eq) = eq {
             equations.push(eq);
         }
     }
-    equations.sort();
-    let first_text = format!("{}", equations[0]);
-    level.equations = equations.into_iter().take(6).collect::<Vec<_>>();
+    equations
+}
 
-    commands.spawn(ActiveEquation(0));
-    commands.spawn(Board::new(first_text));
+/// A level made only of due reviews
+fn review_level(themes: &[BackgroundTheme], mut equations: Vec<Equation>) -> EquationLevel {
+    let seed = random_range(0..u64::MAX);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    equations.sort();
+    EquationLevel {
+        equations,
+        theme: *themes.choose(&mut rng).unwrap(),
+        seed,
+        lives: LIVES,
+        answered: None,
+    }
 }
 
 fn on_equation(
     trigger: Trigger<OnAdd, ActiveEquation>,
     q: Query<&ActiveEquation>,
     level: Res<EquationLevel>,
+    time: Res<Time>,
+    mut campaign: ResMut<Campaign>,
+    mut completed: EventWriter<LevelCompleted>,
+    mut commands: Commands,
 ) {
     let index = q.get(trigger.target()).unwrap();
+    commands
+        .entity(trigger.target())
+        .insert(AskedAt(time.elapsed_secs()));
     match level.equations.get(index.0) {
-        None => {}
-        Some(equation) => {
-            println!("Ready to test user with {}", equation);
+        Some(equation) => debug!("ready to test user with {equation}"),
+        // a level with nothing in it wasn't played, so it doesn't count
+        None if index.0 == level.equations.len() && !level.equations.is_empty() => {
+            campaign.completed += 1;
+            completed.write(LevelCompleted);
         }
+        None => warn!("level has no equation {}", index.0),
     }
 }
 
-#[derive(Resource, Default)]
+const LIVES: u8 = 3;
+const LEVEL_LEN: usize = 6;
+
+/// Goes back to the start menu on escape, or on enter once the level is over. A level that's
+/// left part way through is kept to continue from the start menu.
+fn leave_level(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut level: ResMut<EquationLevel>,
+    active: Query<&ActiveEquation>,
+    mut next_state: ResMut<NextState<GameMode>>,
+    mut commands: Commands,
+) {
+    let Ok(active) = active.single() else {
+        return;
+    };
+    let in_progress = level.in_progress(active.0);
+    let finished = !in_progress && keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]);
+    if !keys.just_pressed(KeyCode::Escape) && !finished {
+        return;
+    }
+
+    if in_progress {
+        commands.insert_resource(ResumeLevel {
+            level: std::mem::take(&mut level),
+            active: active.0,
+        });
+    }
+    next_state.set(GameMode::StartMenu);
+}
+
+/// Sent whenever the player submits an answer to the active equation
+#[derive(Event, Debug)]
+pub struct AnswerSubmitted {
+    // of the equation in the level
+    pub index: usize,
+    pub correct: bool,
+    pub given: i16,
+    pub expected: i16,
+    pub difficulty: u16,
+    // since the equation was shown or last answered
+    pub response_secs: f32,
+    // the first answer given to this equation in the level, rather than a retry
+    pub first_attempt: bool,
+    // after the steps to the answer were shown
+    pub solution_shown: bool,
+}
+
+/// Sent once the last equation of a level has been answered
+#[derive(Event, Debug)]
+pub struct LevelCompleted;
+
+/// How far the player has got through the campaign. Every level completed unlocks another
+/// background theme.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Campaign {
+    pub completed: u32,
+}
+
+impl Campaign {
+    /// The themes new levels can be set in, starting with just the first
+    pub fn unlocked_themes(&self) -> &'static [BackgroundTheme] {
+        let all: &'static [BackgroundTheme] = &BackgroundTheme::ALL;
+        &all[..(self.completed as usize + 1).min(all.len())]
+    }
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
 pub struct EquationLevel {
     equations: Vec<Equation>,
+    theme: BackgroundTheme,
+    seed: u64,
+    pub lives: u8,
+    // the equation last answered, to tell a first attempt from a retry
+    #[serde(default)]
+    answered: Option<usize>,
+}
+
+impl EquationLevel {
+    pub fn equation(&self, index: usize) -> Option<&Equation> {
+        self.equations.get(index)
+    }
+
+    /// Whether there's still an equation to answer at `index`
+    pub fn in_progress(&self, index: usize) -> bool {
+        self.lives > 0 && index < self.equations.len()
+    }
+
+    /// Whether an answer to the equation at `index` would be the first one given to it
+    pub fn first_attempt(&self, index: usize) -> bool {
+        self.answered != Some(index)
+    }
+
+    pub fn mark_answered(&mut self, index: usize) {
+        self.answered = Some(index);
+    }
+}
+
+/// A level loaded from a save, played instead of a new one the next time the game starts
+#[derive(Resource, Debug, Deserialize)]
+pub struct ResumeLevel {
+    pub level: EquationLevel,
+    pub active: usize,
 }
 
 #[derive(Component)]
 pub struct ActiveEquation(pub usize);
+
+/// When the active equation was put to the player, in seconds since startup
+#[derive(Component)]
+pub struct AskedAt(pub f32);
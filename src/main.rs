@@ -1,50 +1,94 @@
+use audio::SoundPlugin;
 use background::BackgroundTiles;
 use banners::BannersPlugin;
 use bevy::log::{Level, LogPlugin};
 use bevy::prelude::*;
 use camera::CameraSetup;
+use layout::LayoutPlugin;
 use level::Levels;
+use menu::MenuPlugin;
+use panel::PanelPlugin;
+use profile::ProfilePlugin;
+use read_aloud::ReadAloudPlugin;
+use review::ReviewPlugin;
+use save::SavePlugin;
 use seed::SeedPlugin;
+use settings::{Settings, SettingsPlugin};
+use solution::SolutionPlugin;
 use sprite_animation::SpriteAnimationPlugin;
+use stats::StatsPlugin;
+use treasure::TreasurePlugin;
+use tween::TweenPlugin;
 
+mod answer;
+mod audio;
 mod background;
 mod banners;
 mod boxes;
 mod camera;
-mod eq_gen;
+mod layout;
 mod level;
+mod menu;
 mod mode;
+mod panel;
 mod player;
+mod profile;
+mod read_aloud;
+mod review;
+mod save;
 mod seed;
+mod settings;
+mod solution;
 mod sprite_animation;
+mod stats;
+mod treasure;
+mod tween;
+mod worksheet;
 
 fn main() -> AppExit {
+    let settings = Settings::load();
+
     App::new()
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         name: Some("Game".to_string()),
-                        resolution: (1920., 1080.).into(),
-                        // mode: bevy::window::WindowMode::Fullscreen(MonitorSelection::Primary),
+                        resolution: settings.display.resolution.as_vec2().into(),
+                        mode: settings.display.window_mode(),
                         ..default()
                     }),
                     ..default()
                 })
                 .set(ImagePlugin::default_nearest())
                 .set(LogPlugin {
-                    filter: "info,wgpu_core=warn,wgpu_hal=warn,calculoot=trace".to_string(),
+                    filter: settings.log_filter.0.clone(),
                     level: Level::TRACE,
                     ..Default::default()
                 }),
         )
+        .add_plugins(SettingsPlugin { settings })
         .add_plugins((
             SeedPlugin,
             SpriteAnimationPlugin,
+            TweenPlugin,
             BackgroundTiles,
             BannersPlugin,
             CameraSetup,
+            LayoutPlugin,
+            PanelPlugin,
             Levels,
+            TreasurePlugin,
+            SoundPlugin,
+            ReadAloudPlugin,
+            MenuPlugin,
+        ))
+        .add_plugins((
+            SavePlugin,
+            ProfilePlugin,
+            StatsPlugin,
+            ReviewPlugin,
+            SolutionPlugin,
         ))
         .run()
 }
@@ -0,0 +1,571 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use eq_gen::Worksheet;
+use rand::random_range;
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::background::BackgroundExclusion;
+use crate::banners::BoardOverflow;
+use crate::camera::VirtualCanvas;
+use crate::layout::{Anchored, ScreenAnchor};
+use crate::level::{Campaign, ResumeLevel};
+use crate::mode::GameMode;
+use crate::panel::{NineSlice, Panel, PanelContent, PanelSize};
+use crate::profile::Profiles;
+use crate::review::{ReviewDeck, ReviewSession};
+use crate::settings::{DifficultySettings, DisplayMode, DisplaySettings, InputMethod, Settings};
+use crate::stats::unix_now;
+use crate::treasure::Treasure;
+use crate::worksheet;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuAction>();
+        app.add_observer(render_menu);
+        app.add_systems(OnEnter(GameMode::StartMenu), spawn_start_menu);
+        app.add_systems(OnEnter(GameMode::Settings), spawn_settings_menu);
+        app.add_systems(
+            Update,
+            (
+                navigate_menus,
+                (on_start_menu, on_settings_menu, refresh_settings_menu),
+                update_menu_text,
+            )
+                .chain(),
+        );
+    }
+}
+
+const MENU_FONT_SIZE: f32 = 16.0;
+const MENU_PADDING: Vec2 = Vec2::new(24.0, 16.0);
+
+/// A titled list of options picked with the arrow keys and enter
+#[derive(Component, Debug)]
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<String>,
+    pub selected: usize,
+    // ignores input, eg. while text is being typed
+    pub locked: bool,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+            selected: 0,
+            locked: false,
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("{}\n", self.title);
+        for (i, item) in self.items.iter().enumerate() {
+            let cursor = if i == self.selected { '>' } else { ' ' };
+            text.push_str(&format!("\n{cursor} {item}"));
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuInput {
+    Select,
+    Back,
+    Left,
+    Right,
+}
+
+/// Sent when the player acts on the selected item of a menu
+#[derive(Event, Debug)]
+pub struct MenuAction {
+    pub menu: Entity,
+    pub item: usize,
+    pub input: MenuInput,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+fn render_menu(
+    trigger: Trigger<OnAdd, Menu>,
+    menus: Query<&Menu>,
+    canvas: Res<VirtualCanvas>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut commands: Commands,
+) {
+    let menu = menus.get(trigger.target()).unwrap();
+    let slice = NineSlice::from_grid(
+        asset_server.load("board.png"),
+        UVec2::new(31, 33),
+        &mut texture_atlas_layouts,
+    );
+    let panel = Panel::new(
+        slice,
+        PanelSize::Content {
+            padding: MENU_PADDING,
+            min_tiles: UVec2::new(4, 3),
+        },
+    );
+    let anchored = Anchored::new(
+        ScreenAnchor::Center,
+        Vec2::ZERO,
+        panel.tiles(None).as_vec2() * panel.slice.tile_size,
+    );
+    let bounds = anchored.rect(canvas.size);
+
+    commands
+        .entity(trigger.target())
+        .insert((
+            Transform::from_translation(bounds.min.extend(5.0)),
+            BackgroundExclusion(bounds),
+            anchored,
+            panel,
+        ))
+        .with_child((
+            MenuText,
+            PanelContent,
+            Text2d::new(menu.text()),
+            TextFont {
+                font: asset_server.load("monogram-extended.ttf"),
+                font_size: MENU_FONT_SIZE,
+                font_smoothing: bevy::text::FontSmoothing::None,
+                ..Default::default()
+            },
+            TextColor(Color::srgb(51.0 / 255.0, 50.0 / 255.0, 61.0 / 255.0)),
+            Anchor::CenterLeft,
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ));
+}
+
+fn update_menu_text(
+    menus: Query<(&Menu, &Children), Changed<Menu>>,
+    mut texts: Query<&mut Text2d, With<MenuText>>,
+) {
+    for (menu, children) in menus.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.0 = menu.text();
+            }
+        }
+    }
+}
+
+fn navigate_menus(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut menus: Query<(Entity, &mut Menu)>,
+    mut actions: EventWriter<MenuAction>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    for (entity, mut menu) in menus.iter_mut() {
+        let count = menu.items.len();
+        if count == 0 || menu.locked {
+            continue;
+        }
+        for key in keys.get_just_pressed() {
+            let input = match key {
+                KeyCode::ArrowUp => {
+                    menu.selected = (menu.selected + count - 1) % count;
+                    sfx.write(PlaySfx(Sfx::Click));
+                    continue;
+                }
+                KeyCode::ArrowDown => {
+                    menu.selected = (menu.selected + 1) % count;
+                    sfx.write(PlaySfx(Sfx::Click));
+                    continue;
+                }
+                KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space => MenuInput::Select,
+                KeyCode::Escape => MenuInput::Back,
+                KeyCode::ArrowLeft => MenuInput::Left,
+                KeyCode::ArrowRight => MenuInput::Right,
+                _ => continue,
+            };
+            sfx.write(PlaySfx(Sfx::Click));
+            actions.write(MenuAction {
+                menu: entity,
+                item: menu.selected,
+                input,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartItem {
+    Continue,
+    NewGame,
+    Review,
+    Profile,
+    Stats,
+    Settings,
+    Worksheet,
+    Quit,
+}
+
+impl StartItem {
+    fn label(&self, profiles: &Profiles) -> String {
+        match self {
+            Self::Continue => "Continue".to_string(),
+            Self::NewGame => "New game".to_string(),
+            Self::Profile => format!("Profile: {}", profiles.active().name),
+            Self::Review => "Review".to_string(),
+            Self::Stats => "Stats".to_string(),
+            Self::Settings => "Settings".to_string(),
+            Self::Worksheet => "Print worksheet".to_string(),
+            Self::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+/// The start menu and the items it was opened with
+#[derive(Component)]
+struct StartMenu {
+    items: Vec<StartItem>,
+    // "New game" was selected once and wipes the campaign if selected again
+    confirm_new_game: bool,
+}
+
+fn spawn_start_menu(
+    profiles: Res<Profiles>,
+    campaign: Res<Campaign>,
+    treasure: Res<Treasure>,
+    deck: Res<ReviewDeck>,
+    resume: Option<Res<ResumeLevel>>,
+    mut commands: Commands,
+) {
+    let mut items = vec![
+        StartItem::NewGame,
+        StartItem::Profile,
+        StartItem::Stats,
+        StartItem::Settings,
+        StartItem::Worksheet,
+        StartItem::Quit,
+    ];
+    if deck.due_count(unix_now()) > 0 {
+        items.insert(1, StartItem::Review);
+    }
+    if resume.is_some() || campaign.completed > 0 || treasure.gold > 0 {
+        items.insert(0, StartItem::Continue);
+    }
+
+    commands.spawn((
+        Menu::new(
+            "Calculoot",
+            items.iter().map(|item| item.label(&profiles)).collect(),
+        ),
+        StartMenu {
+            items,
+            confirm_new_game: false,
+        },
+        StateScoped(GameMode::StartMenu),
+    ));
+}
+
+fn on_start_menu(
+    mut actions: EventReader<MenuAction>,
+    profiles: Res<Profiles>,
+    mut menus: Query<(&mut StartMenu, &mut Menu)>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<GameMode>>,
+    mut exit: EventWriter<AppExit>,
+    mut commands: Commands,
+) {
+    for action in actions.read() {
+        let Ok((mut start, mut menu)) = menus.get_mut(action.menu) else {
+            continue;
+        };
+        let item = start.items[action.item];
+        if start.confirm_new_game
+            && (item != StartItem::NewGame || action.input != MenuInput::Select)
+        {
+            start.confirm_new_game = false;
+            if let Some(i) = start.items.iter().position(|i| *i == StartItem::NewGame) {
+                menu.items[i] = StartItem::NewGame.label(&profiles);
+            }
+        }
+        if action.input != MenuInput::Select {
+            continue;
+        }
+        match item {
+            StartItem::Continue => next_state.set(GameMode::InGame),
+            // starting over loses the campaign, so it takes a second press when there is one
+            StartItem::NewGame
+                if start.items.contains(&StartItem::Continue) && !start.confirm_new_game =>
+            {
+                start.confirm_new_game = true;
+                menu.items[action.item] = "New game? Progress is lost, press enter again".into();
+            }
+            StartItem::NewGame => {
+                commands.remove_resource::<ResumeLevel>();
+                commands.insert_resource(Campaign::default());
+                commands.insert_resource(Treasure::default());
+                next_state.set(GameMode::InGame);
+            }
+            StartItem::Profile => next_state.set(GameMode::Profiles),
+            StartItem::Review => {
+                commands.insert_resource(ReviewSession);
+                next_state.set(GameMode::InGame);
+            }
+            StartItem::Stats => next_state.set(GameMode::Stats),
+            StartItem::Settings => next_state.set(GameMode::Settings),
+            StartItem::Worksheet => {
+                menu.title = export_worksheet(&settings.difficulty);
+            }
+            StartItem::Quit => {
+                exit.write(AppExit::Success);
+            }
+        }
+    }
+}
+
+// how many equations a printed worksheet asks
+const WORKSHEET_LEN: usize = 30;
+
+/// Writes a worksheet at the current difficulty, returning what to tell the player
+fn export_worksheet(difficulty: &DifficultySettings) -> String {
+    let count = WORKSHEET_LEN;
+    let mut worksheet = Worksheet::generate(
+        difficulty.operation_config(),
+        difficulty.max_operations,
+        count,
+        random_range(0..u64::MAX),
+    );
+    worksheet.parentheses = difficulty.parentheses();
+    let made = worksheet.set.equations.len();
+    if made == 0 {
+        warn!("no equations fit the difficulty for a worksheet");
+        return "No equations fit the difficulty".to_string();
+    }
+    let result = worksheet::default_dir()
+        .ok_or("no documents directory".to_string())
+        .and_then(|dir| worksheet::export(&worksheet, &dir));
+    match result {
+        Ok((sheet, _)) if made < count => {
+            warn!(
+                "only fit {made} of {count} equations on {}",
+                sheet.display()
+            );
+            format!("Worksheet of {made} equations saved to your documents")
+        }
+        Ok((sheet, _)) => {
+            info!("saved worksheet to {}", sheet.display());
+            "Worksheet saved to your documents".to_string()
+        }
+        Err(e) => {
+            warn!("could not save worksheet: {e}");
+            "Could not save the worksheet".to_string()
+        }
+    }
+}
+
+/// A line on the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsRow {
+    Window,
+    Resolution,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Mute,
+    LargestAnswer,
+    Operations,
+    Negatives,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    OrderOfOperations,
+    AnswerKeys,
+    ReadAloud,
+    LongEquations,
+    Back,
+}
+
+impl SettingsRow {
+    const ALL: [Self; 18] = [
+        Self::Window,
+        Self::Resolution,
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::Mute,
+        Self::LargestAnswer,
+        Self::Operations,
+        Self::Negatives,
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::OrderOfOperations,
+        Self::AnswerKeys,
+        Self::ReadAloud,
+        Self::LongEquations,
+        Self::Back,
+    ];
+
+    fn label(&self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        let percent = |volume: f32| format!("{}%", (volume * 100.0).round());
+        match self {
+            Self::Window => format!("Window: {:?}", settings.display.mode),
+            Self::Resolution => {
+                let size = settings.display.resolution;
+                format!("Resolution: {}x{}", size.x, size.y)
+            }
+            Self::MasterVolume => format!("Master volume: {}", percent(settings.audio.master)),
+            Self::MusicVolume => format!("Music volume: {}", percent(settings.audio.music)),
+            Self::SfxVolume => format!("Effects volume: {}", percent(settings.audio.sfx)),
+            Self::Mute => format!("Mute: {}", on_off(settings.audio.muted)),
+            Self::LargestAnswer => format!("Largest answer: {}", settings.difficulty.answer_max),
+            Self::Operations => format!(
+                "Operations per question: up to {}",
+                settings.difficulty.max_operations
+            ),
+            Self::Negatives => format!(
+                "Negative numbers: {}",
+                on_off(settings.difficulty.negatives)
+            ),
+            Self::Add => format!("Addition: {}", on_off(settings.difficulty.add)),
+            Self::Subtract => format!("Subtraction: {}", on_off(settings.difficulty.subtract)),
+            Self::Multiply => format!("Multiplication: {}", on_off(settings.difficulty.multiply)),
+            Self::Divide => format!("Division: {}", on_off(settings.difficulty.divide)),
+            Self::OrderOfOperations => format!(
+                "Order of operations: {}",
+                on_off(settings.difficulty.order_of_operations)
+            ),
+            Self::AnswerKeys => format!("Answer keys: {:?}", settings.input),
+            Self::ReadAloud => format!(
+                "Read equations aloud: {}",
+                on_off(settings.accessibility.read_aloud)
+            ),
+            Self::LongEquations => format!(
+                "Long equations: {:?}",
+                settings.accessibility.board_overflow
+            ),
+            Self::Back => "Back".to_string(),
+        }
+    }
+
+    /// Steps the setting forwards or backwards
+    fn adjust(&self, settings: &mut Settings, delta: i32) {
+        let volume =
+            |v: &mut f32| *v = ((*v * 10.0).round() + delta as f32).clamp(0.0, 10.0) / 10.0;
+        match self {
+            Self::Window => {
+                use DisplayMode::*;
+                settings.display.mode = cycle(
+                    &[Windowed, Borderless, Fullscreen],
+                    settings.display.mode,
+                    delta,
+                );
+            }
+            Self::Resolution => {
+                settings.display.resolution = cycle(
+                    &DisplaySettings::RESOLUTIONS,
+                    settings.display.resolution,
+                    delta,
+                );
+            }
+            Self::MasterVolume => volume(&mut settings.audio.master),
+            Self::MusicVolume => volume(&mut settings.audio.music),
+            Self::SfxVolume => volume(&mut settings.audio.sfx),
+            Self::Mute => settings.audio.muted = !settings.audio.muted,
+            Self::LargestAnswer => {
+                settings.difficulty.answer_max = cycle(
+                    &DifficultySettings::ANSWER_MAXES,
+                    settings.difficulty.answer_max,
+                    delta,
+                );
+            }
+            Self::Operations => {
+                settings.difficulty.max_operations =
+                    cycle(&[1, 2, 3], settings.difficulty.max_operations, delta);
+            }
+            Self::Negatives => settings.difficulty.negatives = !settings.difficulty.negatives,
+            Self::Add => settings.difficulty.add = !settings.difficulty.add,
+            Self::Subtract => settings.difficulty.subtract = !settings.difficulty.subtract,
+            Self::Multiply => settings.difficulty.multiply = !settings.difficulty.multiply,
+            Self::Divide => settings.difficulty.divide = !settings.difficulty.divide,
+            Self::OrderOfOperations => {
+                settings.difficulty.order_of_operations = !settings.difficulty.order_of_operations;
+            }
+            Self::AnswerKeys => {
+                use InputMethod::*;
+                settings.input = cycle(&[Any, NumberRow, Numpad], settings.input, delta);
+            }
+            Self::ReadAloud => {
+                settings.accessibility.read_aloud = !settings.accessibility.read_aloud;
+            }
+            Self::LongEquations => {
+                use BoardOverflow::*;
+                settings.accessibility.board_overflow = cycle(
+                    &[Wrap, Shrink],
+                    settings.accessibility.board_overflow,
+                    delta,
+                );
+            }
+            Self::Back => {}
+        }
+    }
+}
+
+/// The option `delta` steps away from `current`, wrapping around at either end
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, delta: i32) -> T {
+    let index = options.iter().position(|o| *o == current).unwrap_or(0) as i32;
+    options[(index + delta).rem_euclid(options.len() as i32) as usize]
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+fn settings_items(settings: &Settings) -> Vec<String> {
+    SettingsRow::ALL
+        .iter()
+        .map(|row| row.label(settings))
+        .collect()
+}
+
+fn spawn_settings_menu(settings: Res<Settings>, mut commands: Commands) {
+    commands.spawn((
+        SettingsMenu,
+        Menu::new("Settings", settings_items(&settings)),
+        StateScoped(GameMode::Settings),
+    ));
+}
+
+fn on_settings_menu(
+    mut actions: EventReader<MenuAction>,
+    menus: Query<(), With<SettingsMenu>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameMode>>,
+) {
+    for action in actions.read() {
+        if !menus.contains(action.menu) {
+            continue;
+        }
+        let row = SettingsRow::ALL[action.item];
+        match (action.input, row) {
+            (MenuInput::Back, _) | (MenuInput::Select, SettingsRow::Back) => {
+                next_state.set(GameMode::StartMenu);
+            }
+            (MenuInput::Left, _) => row.adjust(&mut settings, -1),
+            (MenuInput::Right | MenuInput::Select, _) => row.adjust(&mut settings, 1),
+        }
+    }
+}
+
+fn refresh_settings_menu(settings: Res<Settings>, mut menus: Query<&mut Menu, With<SettingsMenu>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut menu in menus.iter_mut() {
+        let items = settings_items(&settings);
+        if menu.items != items {
+            menu.items = items;
+        }
+    }
+}
@@ -6,5 +6,8 @@ pub enum GameMode {
     #[default]
     Startup,
     StartMenu,
+    Settings,
+    Profiles,
+    Stats,
     InGame,
 }
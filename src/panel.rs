@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+
+use crate::layout::Anchored;
+
+pub struct PanelPlugin;
+
+impl Plugin for PanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (render_panels, (size_anchored, center_content)).chain(),
+        );
+    }
+}
+
+/// An image split into a 3x3 grid of tiles: corners, edges and a fill for the middle
+#[derive(Debug, Clone)]
+pub struct NineSlice {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub tile_size: Vec2,
+}
+
+impl NineSlice {
+    pub fn from_grid(
+        image: Handle<Image>,
+        tile_size: UVec2,
+        texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> Self {
+        Self {
+            image,
+            layout: texture_atlas_layouts
+                .add(TextureAtlasLayout::from_grid(tile_size, 3, 3, None, None)),
+            tile_size: tile_size.as_vec2(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(unused)]
+pub enum PanelSize {
+    Tiles(UVec2),
+    // rounded up to whole tiles
+    Pixels(Vec2),
+    // grows and shrinks with the measured size of `PanelContent` children
+    Content { padding: Vec2, min_tiles: UVec2 },
+}
+
+/// A resizable panel drawn from a nine-slice. Like anchored elements, the panel's transform is
+/// its bottom left corner. Any `Anchored` on the same entity is kept the same size as the panel.
+#[derive(Component, Debug, Clone)]
+#[require(Transform, Visibility)]
+pub struct Panel {
+    pub slice: NineSlice,
+    pub size: PanelSize,
+}
+
+impl Panel {
+    pub fn new(slice: NineSlice, size: PanelSize) -> Self {
+        Self { slice, size }
+    }
+
+    /// How many tiles wide and tall the panel is, given the size of its content
+    pub fn tiles(&self, content: Option<Vec2>) -> UVec2 {
+        let tiles = match self.size {
+            PanelSize::Tiles(tiles) => tiles,
+            PanelSize::Pixels(size) => (size / self.slice.tile_size).ceil().as_uvec2(),
+            PanelSize::Content { padding, min_tiles } => {
+                let size = content.unwrap_or_default() + padding * 2.0;
+                (size / self.slice.tile_size)
+                    .ceil()
+                    .as_uvec2()
+                    .max(min_tiles)
+            }
+        };
+        // there's always room for the corners
+        tiles.max(UVec2::splat(2))
+    }
+}
+
+/// Marks a child of a panel that the panel measures and centers. Content is expected to use a
+/// center left anchor.
+#[derive(Component)]
+pub struct PanelContent;
+
+/// The tiles a panel is currently drawn with
+#[derive(Component, Debug, PartialEq)]
+pub struct PanelGrid(pub UVec2);
+
+#[derive(Component)]
+struct PanelTile;
+
+#[derive(Debug)]
+#[repr(u16)]
+enum PanelTiles {
+    TopLeft = 0,
+    Top = 1,
+    TopRight = 2,
+    Left = 3,
+    Middle = 4,
+    Right = 5,
+    BottomLeft = 6,
+    Bottom = 7,
+    BottomRight = 8,
+}
+
+impl PanelTiles {
+    fn at(x: u32, y: u32, grid: UVec2) -> Self {
+        let left = x == 0;
+        let right = x == grid.x - 1;
+        let bottom = y == 0;
+        let top = y == grid.y - 1;
+
+        match (left, right, bottom, top) {
+            (true, _, _, true) => Self::TopLeft,
+            (_, true, _, true) => Self::TopRight,
+            (_, _, _, true) => Self::Top,
+            (true, _, true, _) => Self::BottomLeft,
+            (_, true, true, _) => Self::BottomRight,
+            (_, _, true, _) => Self::Bottom,
+            (true, _, _, _) => Self::Left,
+            (_, true, _, _) => Self::Right,
+            _ => Self::Middle,
+        }
+    }
+}
+
+/// Measures the content of a panel, the widest and tallest of its content children
+fn content_size(
+    children: Option<&Children>,
+    content: &Query<&TextLayoutInfo, With<PanelContent>>,
+) -> Option<Vec2> {
+    children?
+        .iter()
+        .filter_map(|child| content.get(child).ok())
+        .map(|info| info.size)
+        .reduce(Vec2::max)
+}
+
+type PanelItem<'a> = (
+    Entity,
+    Ref<'a, Panel>,
+    Option<&'a Children>,
+    Option<&'a PanelGrid>,
+);
+
+fn render_panels(
+    panels: Query<PanelItem>,
+    content: Query<&TextLayoutInfo, With<PanelContent>>,
+    tiles: Query<(), With<PanelTile>>,
+    mut commands: Commands,
+) {
+    for (entity, panel, children, current) in panels.iter() {
+        let grid = panel.tiles(content_size(children, &content));
+        if !panel.is_changed() && current.is_some_and(|c| c.0 == grid) {
+            continue;
+        }
+
+        for child in children.into_iter().flatten() {
+            if tiles.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+
+        let tile_size = panel.slice.tile_size;
+        commands.entity(entity).insert(PanelGrid(grid));
+        commands.entity(entity).with_children(|parent| {
+            for x in 0..grid.x {
+                for y in 0..grid.y {
+                    let pos = (Vec2::new(x as f32, y as f32) + 0.5) * tile_size;
+                    parent.spawn((
+                        PanelTile,
+                        Sprite {
+                            image: panel.slice.image.clone(),
+                            texture_atlas: Some(TextureAtlas {
+                                layout: panel.slice.layout.clone(),
+                                index: PanelTiles::at(x, y, grid) as usize,
+                            }),
+                            ..Default::default()
+                        },
+                        Transform::from_translation(pos.extend(0.0)),
+                    ));
+                }
+            }
+        });
+    }
+}
+
+fn size_anchored(mut panels: Query<(Ref<PanelGrid>, &Panel, &mut Anchored)>) {
+    for (grid, panel, mut anchored) in panels.iter_mut() {
+        let size = grid.0.as_vec2() * panel.slice.tile_size;
+        if grid.is_changed() && anchored.size != size {
+            anchored.size = size;
+        }
+    }
+}
+
+fn center_content(
+    panels: Query<(Ref<PanelGrid>, &Panel)>,
+    mut content: Query<(Ref<TextLayoutInfo>, &mut Transform, &ChildOf), With<PanelContent>>,
+) {
+    for (info, mut transform, parent) in content.iter_mut() {
+        let Ok((grid, panel)) = panels.get(parent.parent()) else {
+            continue;
+        };
+        if !info.is_changed() && !grid.is_changed() {
+            continue;
+        }
+        let size = grid.0.as_vec2() * panel.slice.tile_size;
+        transform.translation.x = ((size.x - info.size.x) / 2.0).round();
+        transform.translation.y = (size.y / 2.0).round();
+    }
+}
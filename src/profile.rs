@@ -0,0 +1,491 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::menu::{Menu, MenuAction, MenuInput};
+use crate::mode::GameMode;
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profiles::load());
+        app.add_event::<ProfileSelected>();
+        app.add_event::<GradeChanged>();
+        app.add_systems(Startup, announce_profile);
+        app.add_systems(OnEnter(GameMode::Profiles), spawn_profiles_menu);
+        app.add_systems(
+            Update,
+            (
+                edit_name,
+                on_profiles_menu,
+                refresh_profiles_menu,
+                save_profiles.run_if(resource_changed::<Profiles>),
+            )
+                .chain()
+                .run_if(in_state(GameMode::Profiles)),
+        );
+    }
+}
+
+const PROFILES_FILE: &str = "profiles.toml";
+const MAX_NAME_LEN: usize = 12;
+
+/// The colour a player is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum AvatarColor {
+    #[default]
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl AvatarColor {
+    pub const ALL: [Self; 6] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Red => Color::srgb_u8(190, 48, 48),
+            Self::Orange => Color::srgb_u8(214, 110, 34),
+            Self::Yellow => Color::srgb_u8(178, 140, 20),
+            Self::Green => Color::srgb_u8(52, 130, 60),
+            Self::Blue => Color::srgb_u8(48, 90, 180),
+            Self::Purple => Color::srgb_u8(120, 60, 160),
+        }
+    }
+}
+
+/// A player sharing the computer, with their own save, settings and statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    // names the profile's directory, never reused
+    pub id: u32,
+    pub name: String,
+    pub color: AvatarColor,
+    // 0 is kindergarten
+    pub grade: u8,
+}
+
+impl Profile {
+    pub const MAX_GRADE: u8 = 6;
+
+    pub fn grade_label(&self) -> String {
+        match self.grade {
+            0 => "K".to_string(),
+            grade => grade.to_string(),
+        }
+    }
+}
+
+/// Every profile on this machine and which one is playing
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    // the id of the active profile
+    pub selected: u32,
+    // the id the next new profile gets, so a deleted profile's id is never handed out again
+    #[serde(default)]
+    next_id: u32,
+}
+
+impl Profiles {
+    fn data_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("calculoot"))
+    }
+
+    /// Reads the profiles file, making a first profile if there isn't one yet
+    fn load() -> Self {
+        let loaded = Self::data_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(PROFILES_FILE)).ok())
+            .and_then(|text| match toml::from_str::<Self>(&text) {
+                Ok(profiles) => Some(profiles),
+                Err(e) => {
+                    warn!("ignoring invalid profiles file: {e}");
+                    None
+                }
+            });
+        if let Some(profiles) = loaded.filter(|p| !p.profiles.is_empty()) {
+            return profiles;
+        }
+
+        let mut profiles = Self {
+            profiles: Vec::new(),
+            selected: 0,
+            next_id: 1,
+        };
+        let first = profiles.create();
+        profiles.selected = first;
+        if let Err(e) = profiles.save() {
+            warn!("could not save profiles: {e}");
+        }
+        profiles
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let dir = Self::data_dir().ok_or("no data directory")?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(dir.join(PROFILES_FILE), text).map_err(|e| e.to_string())
+    }
+
+    /// The directory a profile's files are kept in
+    pub fn dir(&self, id: u32) -> Option<PathBuf> {
+        Self::data_dir().map(|dir| dir.join("profiles").join(id.to_string()))
+    }
+
+    pub fn active(&self) -> &Profile {
+        self.profiles
+            .iter()
+            .find(|p| p.id == self.selected)
+            .unwrap_or(&self.profiles[0])
+    }
+
+    /// The directory the active profile's files are kept in
+    pub fn active_dir(&self) -> Option<PathBuf> {
+        self.dir(self.active().id)
+    }
+
+    /// Adds a new profile with a default name and returns its id
+    fn create(&mut self) -> u32 {
+        // files from before the counter was kept start it after the highest id left
+        let id = self
+            .profiles
+            .iter()
+            .map(|p| p.id + 1)
+            .fold(self.next_id.max(1), u32::max);
+        self.next_id = id + 1;
+        let color = AvatarColor::ALL[self.profiles.len() % AvatarColor::ALL.len()];
+        self.profiles.push(Profile {
+            id,
+            name: format!("Player {}", self.profiles.len() + 1),
+            color,
+            grade: 2,
+        });
+        id
+    }
+
+    fn get_mut(&mut self, id: u32) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.id == id)
+    }
+}
+
+/// Sent when a profile becomes the active one, including the one active at startup
+#[derive(Event, Debug)]
+pub struct ProfileSelected;
+
+/// Sent when a profile's grade changes so its difficulty can follow
+#[derive(Event, Debug)]
+pub struct GradeChanged {
+    pub id: u32,
+    pub grade: u8,
+}
+
+fn announce_profile(profiles: Res<Profiles>, mut selected: EventWriter<ProfileSelected>) {
+    debug!("playing as {}", profiles.active().name);
+    selected.write(ProfileSelected);
+}
+
+fn save_profiles(profiles: Res<Profiles>) {
+    if let Err(e) = profiles.save() {
+        warn!("could not save profiles: {e}");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProfilesPage {
+    List,
+    Edit { id: u32, confirm_delete: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditItem {
+    Play,
+    Name,
+    Color,
+    Grade,
+    Delete,
+    Back,
+}
+
+impl EditItem {
+    const ALL: [Self; 6] = [
+        Self::Play,
+        Self::Name,
+        Self::Color,
+        Self::Grade,
+        Self::Delete,
+        Self::Back,
+    ];
+}
+
+/// The profiles screen, listing every profile or editing one of them
+#[derive(Component)]
+struct ProfilesMenu {
+    page: ProfilesPage,
+    // the name is being typed
+    editing_name: bool,
+}
+
+impl ProfilesMenu {
+    fn title(&self, profiles: &Profiles) -> String {
+        match self.page {
+            ProfilesPage::List => "Profiles".to_string(),
+            ProfilesPage::Edit { id, .. } => profiles
+                .profiles
+                .iter()
+                .find(|p| p.id == id)
+                .map_or("Profile".to_string(), |p| p.name.clone()),
+        }
+    }
+
+    fn items(&self, profiles: &Profiles) -> Vec<String> {
+        match self.page {
+            ProfilesPage::List => {
+                let mut items = profiles
+                    .profiles
+                    .iter()
+                    .map(|p| {
+                        let active = if p.id == profiles.selected { " *" } else { "" };
+                        format!("{} (grade {}){active}", p.name, p.grade_label())
+                    })
+                    .collect::<Vec<_>>();
+                items.push("New profile".to_string());
+                items.push("Back".to_string());
+                items
+            }
+            ProfilesPage::Edit { id, confirm_delete } => {
+                let Some(profile) = profiles.profiles.iter().find(|p| p.id == id) else {
+                    return Vec::new();
+                };
+                EditItem::ALL
+                    .iter()
+                    .map(|item| match item {
+                        EditItem::Play => format!("Play as {}", profile.name),
+                        EditItem::Name if self.editing_name => format!("Name: {}_", profile.name),
+                        EditItem::Name => format!("Name: {}", profile.name),
+                        EditItem::Color => format!("Colour: {:?}", profile.color),
+                        EditItem::Grade => format!("Grade: {}", profile.grade_label()),
+                        EditItem::Delete if confirm_delete => "Delete? Press enter again".into(),
+                        EditItem::Delete => "Delete".to_string(),
+                        EditItem::Back => "Back".to_string(),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn spawn_profiles_menu(profiles: Res<Profiles>, mut commands: Commands) {
+    let menu = ProfilesMenu {
+        page: ProfilesPage::List,
+        editing_name: false,
+    };
+    commands.spawn((
+        Menu::new(menu.title(&profiles), menu.items(&profiles)),
+        menu,
+        StateScoped(GameMode::Profiles),
+    ));
+}
+
+fn on_profiles_menu(
+    mut actions: EventReader<MenuAction>,
+    mut menus: Query<(&mut ProfilesMenu, &mut Menu)>,
+    mut profiles: ResMut<Profiles>,
+    mut next_state: ResMut<NextState<GameMode>>,
+    mut selected: EventWriter<ProfileSelected>,
+    mut grade_changed: EventWriter<GradeChanged>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    for action in actions.read() {
+        let Ok((mut screen, mut menu)) = menus.get_mut(action.menu) else {
+            continue;
+        };
+
+        match screen.page {
+            ProfilesPage::List => {
+                let count = profiles.profiles.len();
+                match (action.input, action.item) {
+                    (MenuInput::Back, _) => next_state.set(GameMode::StartMenu),
+                    (MenuInput::Select, i) if i < count => {
+                        screen.page = ProfilesPage::Edit {
+                            id: profiles.profiles[i].id,
+                            confirm_delete: false,
+                        };
+                        menu.selected = 0;
+                    }
+                    (MenuInput::Select, i) if i == count => {
+                        let id = profiles.create();
+                        screen.page = ProfilesPage::Edit {
+                            id,
+                            confirm_delete: false,
+                        };
+                        screen.editing_name = true;
+                        menu.locked = true;
+                        menu.selected = 1;
+                    }
+                    (MenuInput::Select, _) => next_state.set(GameMode::StartMenu),
+                    _ => {}
+                }
+            }
+            ProfilesPage::Edit { id, confirm_delete } => {
+                let item = EditItem::ALL[action.item];
+                let delta = match action.input {
+                    MenuInput::Left => -1,
+                    _ => 1,
+                };
+                if item != EditItem::Delete {
+                    screen.page = ProfilesPage::Edit {
+                        id,
+                        confirm_delete: false,
+                    };
+                }
+
+                match (action.input, item) {
+                    (MenuInput::Back, _) | (MenuInput::Select, EditItem::Back) => {
+                        screen.page = ProfilesPage::List;
+                        menu.selected = 0;
+                    }
+                    (MenuInput::Select, EditItem::Play) => {
+                        if profiles.selected != id {
+                            profiles.selected = id;
+                            selected.write(ProfileSelected);
+                        }
+                        next_state.set(GameMode::StartMenu);
+                    }
+                    (MenuInput::Select, EditItem::Name) => {
+                        // the menu ignores keys until the name is done
+                        screen.editing_name = true;
+                        menu.locked = true;
+                    }
+                    (_, EditItem::Color) => {
+                        if let Some(profile) = profiles.get_mut(id) {
+                            let index = AvatarColor::ALL
+                                .iter()
+                                .position(|c| *c == profile.color)
+                                .unwrap_or(0) as i32;
+                            let len = AvatarColor::ALL.len() as i32;
+                            profile.color =
+                                AvatarColor::ALL[(index + delta).rem_euclid(len) as usize];
+                        }
+                    }
+                    (_, EditItem::Grade) => {
+                        if let Some(profile) = profiles.get_mut(id) {
+                            let grades = Profile::MAX_GRADE as i32 + 1;
+                            profile.grade = (profile.grade as i32 + delta).rem_euclid(grades) as u8;
+                            grade_changed.write(GradeChanged {
+                                id,
+                                grade: profile.grade,
+                            });
+                        }
+                    }
+                    (MenuInput::Select, EditItem::Delete) if profiles.profiles.len() == 1 => {
+                        // there always has to be someone playing
+                        sfx.write(PlaySfx(Sfx::Wrong));
+                    }
+                    (MenuInput::Select, EditItem::Delete) if !confirm_delete => {
+                        screen.page = ProfilesPage::Edit {
+                            id,
+                            confirm_delete: true,
+                        };
+                    }
+                    (MenuInput::Select, EditItem::Delete) => {
+                        profiles.profiles.retain(|p| p.id != id);
+                        if let Some(dir) = profiles.dir(id)
+                            && let Err(e) = fs::remove_dir_all(&dir)
+                            && dir.exists()
+                        {
+                            warn!("could not remove {}: {e}", dir.display());
+                        }
+                        if profiles.selected == id {
+                            profiles.selected = profiles.profiles[0].id;
+                            selected.write(ProfileSelected);
+                        }
+                        screen.page = ProfilesPage::List;
+                        menu.selected = 0;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Types into the name of the profile being edited
+fn edit_name(
+    mut keys: EventReader<KeyboardInput>,
+    mut menus: Query<(&mut ProfilesMenu, &mut Menu)>,
+    mut profiles: ResMut<Profiles>,
+) {
+    let Ok((mut screen, mut menu)) = menus.single_mut() else {
+        return;
+    };
+    let ProfilesPage::Edit { id, .. } = screen.page else {
+        return;
+    };
+    if !screen.editing_name {
+        keys.clear();
+        return;
+    }
+    let Some(profile) = profiles.get_mut(id) else {
+        return;
+    };
+
+    for key in keys.read().filter(|k| k.state.is_pressed()) {
+        match &key.logical_key {
+            Key::Enter | Key::Escape => {
+                let name = profile.name.trim().to_string();
+                if !name.is_empty() {
+                    profile.name = name;
+                    screen.editing_name = false;
+                    menu.locked = false;
+                }
+            }
+            Key::Backspace => {
+                profile.name.pop();
+            }
+            Key::Character(text) => {
+                for c in text.chars() {
+                    if (c.is_alphanumeric() || c == ' ')
+                        && profile.name.chars().count() < MAX_NAME_LEN
+                    {
+                        profile.name.push(c);
+                    }
+                }
+            }
+            Key::Space if profile.name.chars().count() < MAX_NAME_LEN => profile.name.push(' '),
+            _ => {}
+        }
+    }
+}
+
+fn refresh_profiles_menu(
+    profiles: Res<Profiles>,
+    mut menus: Query<(Ref<ProfilesMenu>, &mut Menu)>,
+) {
+    for (screen, mut menu) in menus.iter_mut() {
+        if !screen.is_changed() && !profiles.is_changed() {
+            continue;
+        }
+        let title = screen.title(&profiles);
+        let items = screen.items(&profiles);
+        if menu.title != title || menu.items != items {
+            menu.title = title;
+            menu.items = items;
+            menu.selected = menu.selected.min(menu.items.len().saturating_sub(1));
+        }
+    }
+}
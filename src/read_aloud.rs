@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use eq_gen::{Equation, OperationType, Value};
+
+use crate::audio::AudioVolumes;
+use crate::level::{ActiveEquation, EquationLevel};
+
+pub struct ReadAloudPlugin;
+
+impl Plugin for ReadAloudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReadAloud>();
+        app.init_resource::<SpeechQueue>();
+        app.add_observer(on_equation);
+        app.add_systems(Update, (read_on_demand, speak).chain());
+    }
+}
+
+// gap left between the two sides of a nested equation so groups can be heard
+const GROUP_PAUSE_SECS: f32 = 0.25;
+
+/// Reads equations out loud from pre-recorded clips
+#[derive(Resource, Debug, Clone)]
+pub struct ReadAloud {
+    // read each equation as soon as it is shown, otherwise only when asked for
+    pub auto: bool,
+}
+
+impl Default for ReadAloud {
+    fn default() -> Self {
+        Self { auto: true }
+    }
+}
+
+/// A single pre-recorded clip, or a pause between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Word {
+    // zero to nineteen
+    Unit(u8),
+    // twenty to ninety
+    Tens(u8),
+    Hundred,
+    Thousand,
+    Negative,
+    Plus,
+    Minus,
+    Times,
+    DividedBy,
+    Equals,
+    Pause,
+}
+
+impl Word {
+    const UNITS: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+
+    /// The word as written, which is also the name of its clip
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Unit(n) => Self::UNITS[*n as usize],
+            Self::Tens(n) => Self::TENS[*n as usize],
+            Self::Hundred => "hundred",
+            Self::Thousand => "thousand",
+            Self::Negative => "negative",
+            Self::Plus => "plus",
+            Self::Minus => "minus",
+            Self::Times => "times",
+            Self::DividedBy => "divided-by",
+            Self::Equals => "equals",
+            Self::Pause => "",
+        }
+    }
+
+    fn clip(&self) -> Option<String> {
+        match self {
+            Self::Pause => None,
+            _ => Some(format!("audio/speech/{}.ogg", self.text())),
+        }
+    }
+}
+
+/// The words for a number, eg. -342 is "negative three hundred forty two"
+pub fn number_words(n: i16) -> Vec<Word> {
+    let mut words = Vec::new();
+    if n < 0 {
+        words.push(Word::Negative);
+    }
+    let n = n.unsigned_abs();
+    if n == 0 {
+        words.push(Word::Unit(0));
+        return words;
+    }
+
+    let thousands = n / 1000;
+    if thousands > 0 {
+        words.extend(below_thousand(thousands));
+        words.push(Word::Thousand);
+    }
+    words.extend(below_thousand(n % 1000));
+    words
+}
+
+fn below_thousand(n: u16) -> Vec<Word> {
+    let mut words = Vec::new();
+    let hundreds = n / 100;
+    if hundreds > 0 {
+        words.push(Word::Unit(hundreds as u8));
+        words.push(Word::Hundred);
+    }
+    match n % 100 {
+        0 => {}
+        rest @ 1..20 => words.push(Word::Unit(rest as u8)),
+        rest => {
+            words.push(Word::Tens((rest / 10) as u8));
+            if rest % 10 > 0 {
+                words.push(Word::Unit((rest % 10) as u8));
+            }
+        }
+    }
+    words
+}
+
+/// The words for an equation read left to right, eg. "seven plus five times two equals"
+pub fn sentence(equation: &Equation) -> Vec<Word> {
+    let mut words = equation_words(equation);
+    words.push(Word::Equals);
+    words
+}
+
+fn equation_words(equation: &Equation) -> Vec<Word> {
+    let mut words = value_words(&equation.left);
+    words.push(match equation.op {
+        OperationType::Add => Word::Plus,
+        OperationType::Subtract => Word::Minus,
+        OperationType::Multiply => Word::Times,
+        OperationType::Divide => Word::DividedBy,
+    });
+    words.extend(value_words(&equation.right));
+    words
+}
+
+fn value_words(value: &Value) -> Vec<Word> {
+    match value {
+        Value::Number(n) => number_words(*n),
+        Value::Equation(e) => {
+            let mut words = vec![Word::Pause];
+            words.extend(equation_words(e));
+            words.push(Word::Pause);
+            words
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Utterance {
+    Clip(Handle<AudioSource>),
+    Pause(Timer),
+}
+
+/// Clips waiting to be played, one after another
+#[derive(Resource, Default)]
+struct SpeechQueue(VecDeque<Utterance>);
+
+/// The clip currently being read out
+#[derive(Component)]
+struct Speech;
+
+/// Reads the active equation when it first appears
+fn on_equation(
+    trigger: Trigger<OnAdd, ActiveEquation>,
+    active: Query<&ActiveEquation>,
+    read_aloud: Res<ReadAloud>,
+    mut commands: Commands,
+) {
+    if !read_aloud.auto {
+        return;
+    }
+    if let Ok(active) = active.get(trigger.target()) {
+        let index = active.0;
+        commands.run_system_cached_with(read_equation, index);
+    }
+}
+
+/// Reads the active equation again whenever R is pressed
+fn read_on_demand(
+    keys: Res<ButtonInput<KeyCode>>,
+    active: Query<&ActiveEquation>,
+    mut commands: Commands,
+) {
+    if keys.just_pressed(KeyCode::KeyR)
+        && let Ok(active) = active.single()
+    {
+        commands.run_system_cached_with(read_equation, active.0);
+    }
+}
+
+/// Replaces anything being read with the equation at `index`
+fn read_equation(
+    index: In<usize>,
+    level: Res<EquationLevel>,
+    asset_server: Res<AssetServer>,
+    speech: Query<Entity, With<Speech>>,
+    mut queue: ResMut<SpeechQueue>,
+    mut commands: Commands,
+) {
+    let Some(equation) = level.equation(*index) else {
+        return;
+    };
+    let words = sentence(equation);
+    debug!(
+        "reading aloud: {}",
+        words
+            .iter()
+            .map(Word::text)
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    for entity in speech.iter() {
+        commands.entity(entity).despawn();
+    }
+    queue.0 = words
+        .iter()
+        .map(|word| match word.clip() {
+            Some(path) => Utterance::Clip(asset_server.load(path)),
+            None => Utterance::Pause(Timer::from_seconds(GROUP_PAUSE_SECS, TimerMode::Once)),
+        })
+        .collect();
+}
+
+/// Plays the next clip once the last one has finished
+fn speak(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    volumes: Res<AudioVolumes>,
+    speech: Query<(Entity, &AudioPlayer), With<Speech>>,
+    mut queue: ResMut<SpeechQueue>,
+    mut commands: Commands,
+) {
+    if let Ok((entity, player)) = speech.single() {
+        // a missing clip would otherwise hold up the rest of the sentence forever
+        if asset_server.load_state(&player.0).is_failed() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    match queue.0.front_mut() {
+        None => return,
+        Some(Utterance::Pause(timer)) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+        }
+        Some(Utterance::Clip(clip)) => {
+            commands.spawn((
+                Speech,
+                AudioPlayer::new(clip.clone()),
+                PlaybackSettings::DESPAWN.with_volume(volumes.sfx()),
+            ));
+        }
+    }
+    queue.0.pop_front();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(words: &[Word]) -> String {
+        words
+            .iter()
+            .map(Word::text)
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn numbers_are_read_as_words() {
+        let cases = [
+            (0, "zero"),
+            (7, "seven"),
+            (13, "thirteen"),
+            (20, "twenty"),
+            (42, "forty two"),
+            (100, "one hundred"),
+            (305, "three hundred five"),
+            (1000, "one thousand"),
+            (1019, "one thousand nineteen"),
+            (-342, "negative three hundred forty two"),
+            (i16::MAX, "thirty two thousand seven hundred sixty seven"),
+            (
+                i16::MIN,
+                "negative thirty two thousand seven hundred sixty eight",
+            ),
+        ];
+        for (n, words) in cases {
+            assert_eq!(text(&number_words(n)), words, "{n}");
+        }
+    }
+
+    #[test]
+    fn every_word_has_a_clip_but_pauses() {
+        for n in [0, 19, 90, -1000] {
+            for word in number_words(n) {
+                assert_eq!(
+                    word.clip(),
+                    Some(format!("audio/speech/{}.ogg", word.text()))
+                );
+            }
+        }
+        assert_eq!(Word::Pause.clip(), None);
+    }
+
+    #[test]
+    fn sentences_pause_around_nested_equations() {
+        let times = Equation::new(5.into(), OperationType::Multiply, 2.into(), 10);
+        let eq = Equation::new(7.into(), OperationType::Add, Value::Equation(times), 17);
+        let words = sentence(&eq);
+        assert_eq!(text(&words), "seven plus five times two equals");
+        let pauses = words.iter().filter(|w| **w == Word::Pause).count();
+        assert_eq!(pauses, 2);
+        assert_eq!(words.last(), Some(&Word::Equals));
+    }
+}
@@ -0,0 +1,299 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use eq_gen::{Equation, Versioned};
+use serde::{Deserialize, Serialize};
+
+use crate::level::{AnswerSubmitted, EquationLevel};
+use crate::mode::GameMode;
+use crate::profile::{ProfileSelected, Profiles};
+use crate::save::write_atomically;
+use crate::stats::unix_now;
+
+pub struct ReviewPlugin;
+
+impl Plugin for ReviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReviewDeck>();
+        app.add_systems(PreUpdate, load_deck.run_if(on_event::<ProfileSelected>));
+        app.add_systems(
+            Update,
+            review_answers.run_if(in_state(GameMode::InGame).and(on_event::<AnswerSubmitted>)),
+        );
+        app.add_systems(
+            PostUpdate,
+            save_deck.run_if(resource_changed::<ReviewDeck>.and(not(resource_added::<ReviewDeck>))),
+        );
+    }
+}
+
+const DECK_FILE: &str = "review.json";
+const DAY_SECS: u64 = 24 * 60 * 60;
+// the most due reviews mixed into a new level
+pub const REVIEWS_PER_LEVEL: usize = 2;
+// how many equations a review session asks
+pub const REVIEW_SESSION_LEN: usize = 6;
+
+/// Makes the next level a review session of due equations only
+#[derive(Resource, Debug)]
+pub struct ReviewSession;
+
+/// How well a fact is known, going by how long it can go between reviews
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mastery {
+    // missed since it was last answered right
+    Learning,
+    Familiar,
+    Mastered,
+}
+
+/// A missed equation scheduled for review with SM-2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewCard {
+    // in canonical form
+    pub equation: Equation,
+    pub ease: f32,
+    // correct answers in a row
+    pub repetitions: u32,
+    pub interval_days: u32,
+    // seconds since the unix epoch
+    pub due: u64,
+    pub lapses: u32,
+}
+
+impl ReviewCard {
+    const START_EASE: f32 = 2.5;
+    const MIN_EASE: f32 = 1.3;
+    const MASTERED_DAYS: u32 = 21;
+
+    fn new(equation: Equation, now: u64) -> Self {
+        Self {
+            equation,
+            ease: Self::START_EASE,
+            repetitions: 0,
+            interval_days: 0,
+            due: now,
+            lapses: 0,
+        }
+    }
+
+    /// Reschedules the card after an answer graded from 0 (blackout) to 5 (perfect recall)
+    fn grade(&mut self, quality: u8, now: u64) {
+        let q = quality.min(5) as f32;
+        self.ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(Self::MIN_EASE);
+
+        if quality < 3 {
+            // relearn it straight away, in the next level
+            self.repetitions = 0;
+            self.interval_days = 0;
+            self.lapses += 1;
+            self.due = now;
+            return;
+        }
+
+        self.interval_days = match self.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (self.interval_days as f32 * self.ease).round() as u32,
+        };
+        self.repetitions += 1;
+        self.due = now + self.interval_days as u64 * DAY_SECS;
+    }
+
+    pub fn mastery(&self) -> Mastery {
+        if self.repetitions == 0 {
+            Mastery::Learning
+        } else if self.interval_days >= Self::MASTERED_DAYS {
+            Mastery::Mastered
+        } else {
+            Mastery::Familiar
+        }
+    }
+}
+
+/// SM-2 quality of an answer, faster correct answers showing better recall
+fn answer_quality(answer: &AnswerSubmitted) -> u8 {
+    match (answer.correct, answer.response_secs) {
+        (false, _) => 1,
+        (true, secs) if secs < 5.0 => 5,
+        (true, secs) if secs < 10.0 => 4,
+        (true, _) => 3,
+    }
+}
+
+/// The active profile's missed facts and when each is next due
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewDeck {
+    pub cards: Vec<ReviewCard>,
+}
+
+impl ReviewDeck {
+    fn path(profiles: &Profiles) -> Option<PathBuf> {
+        profiles.active_dir().map(|dir| dir.join(DECK_FILE))
+    }
+
+    fn card_mut(&mut self, equation: &Equation) -> Option<&mut ReviewCard> {
+        self.cards
+            .iter_mut()
+            .find(|card| card.equation == *equation)
+    }
+
+    /// Up to `count` equations due by `now`, the longest overdue first
+    pub fn due(&self, now: u64, count: usize) -> Vec<Equation> {
+        let mut due: Vec<_> = self.cards.iter().filter(|card| card.due <= now).collect();
+        due.sort_by_key(|card| card.due);
+        due.into_iter()
+            .take(count)
+            .map(|card| card.equation.clone())
+            .collect()
+    }
+
+    pub fn due_count(&self, now: u64) -> usize {
+        self.cards.iter().filter(|card| card.due <= now).count()
+    }
+
+    pub fn mastered(&self) -> usize {
+        self.cards
+            .iter()
+            .filter(|card| card.mastery() == Mastery::Mastered)
+            .count()
+    }
+}
+
+fn load_deck(profiles: Res<Profiles>, mut commands: Commands) {
+    let deck = ReviewDeck::path(&profiles)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| match Versioned::<ReviewDeck>::from_json(&text) {
+            Ok(deck) => Some(deck),
+            Err(e) => {
+                warn!("ignoring invalid review deck: {e}");
+                None
+            }
+        })
+        .unwrap_or_default();
+    commands.insert_resource::<ReviewDeck>(deck);
+}
+
+fn save_deck(deck: Res<ReviewDeck>, profiles: Res<Profiles>) {
+    let Some(path) = ReviewDeck::path(&profiles) else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|e| e.to_string())
+        .and_then(|_| Versioned::new(deck.clone()).to_json())
+        .and_then(|text| write_atomically(&path, text).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("could not save review deck: {e}");
+    }
+}
+
+/// Adds missed equations to the deck and reschedules the ones already in it
+fn review_answers(
+    mut submitted: EventReader<AnswerSubmitted>,
+    level: Res<EquationLevel>,
+    mut deck: ResMut<ReviewDeck>,
+) {
+    let now = unix_now();
+    // only the first try is recall, a retry comes after being told it was wrong or shown the
+    // solution and would overwrite the lapse
+    for answer in submitted.read().filter(|answer| answer.first_attempt) {
+        let Some(equation) = level.equation(answer.index) else {
+            continue;
+        };
+        let equation = equation.canonical();
+        let quality = answer_quality(answer);
+        match deck.card_mut(&equation) {
+            Some(card) => card.grade(quality, now),
+            None if !answer.correct => {
+                let mut card = ReviewCard::new(equation, now);
+                card.grade(quality, now);
+                deck.cards.push(card);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eq_gen::{OperationType, SCHEMA_VERSION, Value};
+
+    use super::*;
+
+    const NOW: u64 = 1_000_000;
+
+    fn card() -> ReviewCard {
+        let equation = Equation::new(Value::Number(3), OperationType::Add, Value::Number(4), 7);
+        ReviewCard::new(equation, NOW)
+    }
+
+    #[test]
+    fn decks_load_only_at_a_known_schema() {
+        let deck = ReviewDeck {
+            cards: vec![card()],
+        };
+        let versioned = Versioned::new(deck.clone()).to_json().unwrap();
+        assert_eq!(
+            Versioned::<ReviewDeck>::from_json(&versioned)
+                .unwrap()
+                .cards[0]
+                .equation,
+            card().equation
+        );
+        let newer = versioned.replace(
+            &format!("\"schema\":{SCHEMA_VERSION}"),
+            &format!("\"schema\":{}", SCHEMA_VERSION + 1),
+        );
+        assert!(Versioned::<ReviewDeck>::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn correct_answers_space_out_reviews() {
+        let mut card = card();
+        card.grade(5, NOW);
+        assert_eq!((card.repetitions, card.interval_days), (1, 1));
+        assert_eq!(card.due, NOW + DAY_SECS);
+        assert_eq!(card.mastery(), Mastery::Familiar);
+
+        card.grade(5, NOW);
+        assert_eq!((card.repetitions, card.interval_days), (2, 6));
+
+        // the ease grew by 0.1 for each perfect answer
+        card.grade(5, NOW);
+        assert_eq!(card.interval_days, (6.0 * 2.8_f32).round() as u32);
+        assert_eq!(card.due, NOW + card.interval_days as u64 * DAY_SECS);
+
+        card.grade(5, NOW);
+        assert!(card.interval_days >= ReviewCard::MASTERED_DAYS);
+        assert_eq!(card.mastery(), Mastery::Mastered);
+    }
+
+    #[test]
+    fn a_miss_starts_the_card_over() {
+        let mut card = card();
+        for _ in 0..3 {
+            card.grade(4, NOW);
+        }
+        card.grade(1, NOW);
+        assert_eq!((card.repetitions, card.interval_days), (0, 0));
+        assert_eq!(card.lapses, 1);
+        assert_eq!(card.due, NOW);
+        assert_eq!(card.mastery(), Mastery::Learning);
+    }
+
+    #[test]
+    fn ease_drops_with_poor_recall_but_not_below_the_minimum() {
+        let mut card = card();
+        card.grade(3, NOW);
+        assert!(card.ease < ReviewCard::START_EASE);
+        assert_eq!(card.repetitions, 1);
+        for _ in 0..20 {
+            card.grade(0, NOW);
+        }
+        assert_eq!(card.ease, ReviewCard::MIN_EASE);
+        assert_eq!(card.lapses, 20);
+    }
+}
@@ -0,0 +1,239 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use eq_gen::SCHEMA_VERSION;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::level::{ActiveEquation, AnswerSubmitted, Campaign, EquationLevel, ResumeLevel};
+use crate::mode::GameMode;
+use crate::profile::{ProfileSelected, Profiles};
+use crate::treasure::Treasure;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        // before the start menu is shown, so it knows whether there is anything to continue
+        app.add_systems(PreUpdate, load_game.run_if(on_event::<ProfileSelected>));
+        app.add_systems(
+            PostUpdate,
+            autosave.run_if(
+                in_state(GameMode::InGame).and(
+                    on_event::<AnswerSubmitted>
+                        .or(resource_changed::<Treasure>)
+                        .or(resource_changed::<Campaign>),
+                ),
+            ),
+        );
+    }
+}
+
+const SAVE_FILE: &str = "save.json";
+const SAVE_VERSION: u64 = 1;
+
+/// Upgrades a save from one version to the next, the first entry takes a version 1 save to
+/// version 2 and so on. Add one here whenever the save format changes.
+type Migration = fn(&mut Value);
+const MIGRATIONS: &[Migration] = &[];
+const _: () = assert!(MIGRATIONS.len() as u64 == SAVE_VERSION - 1);
+
+/// Everything written to the save file, as of the current version
+#[derive(Deserialize)]
+struct SaveGame {
+    campaign: Campaign,
+    treasure: Treasure,
+    // the level that was being played, if it wasn't finished
+    level: Option<ResumeLevel>,
+}
+
+/// Each profile has its own save
+fn save_path(profiles: &Profiles) -> Option<PathBuf> {
+    profiles.active_dir().map(|dir| dir.join(SAVE_FILE))
+}
+
+/// Brings an older save up to the current version
+fn migrate(save: Value) -> Result<Value, String> {
+    migrate_with(save, MIGRATIONS)
+}
+
+/// Brings a save up to version `migrations.len() + 1`
+fn migrate_with(mut save: Value, migrations: &[Migration]) -> Result<Value, String> {
+    let current = migrations.len() as u64 + 1;
+    let version = save
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or("missing save version")?;
+    if version == 0 || version > current {
+        return Err(format!("unsupported save version {version}"));
+    }
+    // the equations in the level follow eq_gen's schema, saves from before it was recorded
+    // used the first
+    let schema = save.get("schema").and_then(Value::as_u64).unwrap_or(1);
+    if schema == 0 || schema > SCHEMA_VERSION as u64 {
+        return Err(format!("unsupported equation schema version {schema}"));
+    }
+
+    for migration in &migrations[(version - 1) as usize..] {
+        migration(&mut save);
+    }
+    save["version"] = current.into();
+    save["schema"] = SCHEMA_VERSION.into();
+    Ok(save)
+}
+
+/// Writes a file next to `path` and renames it over the top, so a crash part way through leaves
+/// the old file rather than a truncated one
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+fn read_save(path: &PathBuf) -> Result<SaveGame, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let save = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    serde_json::from_value(migrate(save)?).map_err(|e| e.to_string())
+}
+
+/// Loads the active profile's save, or starts them fresh if they don't have one
+fn load_game(profiles: Res<Profiles>, mut commands: Commands) {
+    commands.insert_resource(Campaign::default());
+    commands.insert_resource(Treasure::default());
+    commands.remove_resource::<ResumeLevel>();
+
+    let Some(path) = save_path(&profiles) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+
+    match read_save(&path) {
+        Ok(save) => {
+            debug!("loaded save from {}", path.display());
+            commands.insert_resource(save.campaign);
+            commands.insert_resource(save.treasure);
+            if let Some(level) = save.level {
+                commands.insert_resource(level);
+            }
+        }
+        Err(e) => {
+            // keep the broken save around rather than overwriting it with a new game
+            warn!("could not load save {}: {e}", path.display());
+            if let Err(e) = fs::rename(&path, path.with_extension("json.bak")) {
+                warn!("could not back up save: {e}");
+            }
+        }
+    }
+}
+
+fn autosave(
+    profiles: Res<Profiles>,
+    campaign: Res<Campaign>,
+    treasure: Res<Treasure>,
+    level: Res<EquationLevel>,
+    active: Query<&ActiveEquation>,
+) {
+    let Some(path) = save_path(&profiles) else {
+        return;
+    };
+    let level = match active.single() {
+        Ok(active) if level.in_progress(active.0) => json!({
+            "level": &*level,
+            "active": active.0,
+        }),
+        _ => Value::Null,
+    };
+    let save = json!({
+        "version": SAVE_VERSION,
+        "schema": SCHEMA_VERSION,
+        "campaign": &*campaign,
+        "treasure": &*treasure,
+        "level": level,
+    });
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| write_atomically(&path, save.to_string()));
+    match result {
+        Ok(()) => debug!("saved game"),
+        Err(e) => warn!("could not save game: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_writes_replace_the_file_and_leave_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("calculoot-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SAVE_FILE);
+
+        write_atomically(&path, "first").unwrap();
+        write_atomically(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // as if version 1 kept the gold at the top and version 2 moved it into the treasure
+    const GOLD_INTO_TREASURE: &[Migration] = &[|save| {
+        let gold = save["gold"].take();
+        save["treasure"] = json!({ "gold": gold });
+    }];
+
+    #[test]
+    fn older_saves_run_every_migration_after_their_version() {
+        let old = json!({
+            "version": 1,
+            "campaign": { "completed": 3 },
+            "gold": 12,
+            "level": null,
+        });
+        let save = migrate_with(old, GOLD_INTO_TREASURE).unwrap();
+        assert_eq!(save["version"], 2);
+        assert_eq!(save["schema"], SCHEMA_VERSION);
+        let save: SaveGame = serde_json::from_value(save).unwrap();
+        assert_eq!(save.campaign.completed, 3);
+        assert_eq!(save.treasure.gold, 12);
+        assert!(save.level.is_none());
+
+        // already up to date, nothing runs
+        let current = json!({
+            "version": 2,
+            "campaign": { "completed": 1 },
+            "treasure": { "gold": 5 },
+            "level": null,
+        });
+        let save = migrate_with(current.clone(), GOLD_INTO_TREASURE).unwrap();
+        assert_eq!(save["treasure"], current["treasure"]);
+    }
+
+    #[test]
+    fn saves_without_a_known_version_are_rejected() {
+        for version in [json!(null), json!(0), json!(SAVE_VERSION + 1)] {
+            let save = json!({
+                "version": version,
+                "campaign": { "completed": 0 },
+                "treasure": { "gold": 0 },
+                "level": null,
+            });
+            assert!(migrate(save).is_err());
+        }
+        let written_now = json!({
+            "version": SAVE_VERSION,
+            "schema": SCHEMA_VERSION,
+            "campaign": { "completed": 2 },
+            "treasure": { "gold": 7 },
+            "level": null,
+        });
+        assert_eq!(migrate(written_now.clone()).unwrap(), written_now);
+    }
+}
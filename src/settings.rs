@@ -0,0 +1,374 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+use eq_gen::{NumberType, OperationConfig, OperationType, Parentheses, Weights};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioVolumes;
+use crate::banners::BoardOverflow;
+use crate::profile::{GradeChanged, ProfileSelected, Profiles};
+use crate::read_aloud::ReadAloud;
+
+pub struct SettingsPlugin {
+    pub settings: Settings,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone());
+        app.add_systems(
+            Update,
+            (
+                on_profile_selected.run_if(on_event::<ProfileSelected>),
+                on_grade_changed.run_if(on_event::<GradeChanged>),
+                from_audio.run_if(resource_changed::<AudioVolumes>),
+                (apply_settings, save_settings)
+                    .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+            )
+                .chain(),
+        );
+        app.add_systems(Startup, apply_settings);
+    }
+}
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+/// Everything the player can change, saved as TOML in the platform config directory. Each profile
+/// also keeps its own copy, which overrides everything but the machine wide display and logging.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub input: InputMethod,
+    pub log_filter: LogFilter,
+    pub display: DisplaySettings,
+    pub audio: AudioVolumes,
+    pub difficulty: DifficultySettings,
+    pub accessibility: AccessibilitySettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    // only used when windowed
+    pub resolution: UVec2,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::default(),
+            resolution: UVec2::new(1920, 1080),
+        }
+    }
+}
+
+impl DisplaySettings {
+    pub const RESOLUTIONS: [UVec2; 4] = [
+        UVec2::new(1280, 720),
+        UVec2::new(1600, 900),
+        UVec2::new(1920, 1080),
+        UVec2::new(2560, 1440),
+    ];
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self.mode {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+            }
+        }
+    }
+}
+
+/// The kinds of equations new levels are made of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultySettings {
+    pub answer_max: i16,
+    pub max_operations: u8,
+    pub negatives: bool,
+    pub add: bool,
+    pub subtract: bool,
+    pub multiply: bool,
+    pub divide: bool,
+    // write equations with only the parentheses precedence needs, and ask ones that working
+    // left to right gets wrong
+    pub order_of_operations: bool,
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self {
+            answer_max: 20,
+            max_operations: 3,
+            negatives: true,
+            add: true,
+            subtract: true,
+            multiply: true,
+            divide: true,
+            order_of_operations: false,
+        }
+    }
+}
+
+impl DifficultySettings {
+    pub const ANSWER_MAXES: [i16; 4] = [10, 20, 50, 100];
+
+    /// A starting point for a player in the given grade, 0 being kindergarten
+    pub fn for_grade(grade: u8) -> Self {
+        let (answer_max, max_operations) = match grade {
+            0 => (10, 1),
+            1 => (20, 1),
+            2 => (50, 2),
+            3 | 4 => (100, 2),
+            _ => (100, 3),
+        };
+        Self {
+            answer_max,
+            max_operations,
+            negatives: grade >= 5,
+            add: true,
+            subtract: true,
+            multiply: grade >= 3,
+            divide: grade >= 4,
+            order_of_operations: grade >= 6,
+        }
+    }
+
+    /// How equations are written on the board
+    pub fn parentheses(&self) -> Parentheses {
+        if self.order_of_operations {
+            Parentheses::Needed
+        } else {
+            Parentheses::All
+        }
+    }
+
+    pub fn operation_config(&self) -> OperationConfig {
+        let mut allowed_operations = Weights::default();
+        for (enabled, op) in [
+            (self.add, OperationType::Add),
+            (self.subtract, OperationType::Subtract),
+            (self.multiply, OperationType::Multiply),
+            (self.divide, OperationType::Divide),
+        ] {
+            if enabled {
+                allowed_operations.insert(op, 1);
+            }
+        }
+        // there has to be something to ask
+        if allowed_operations.is_empty() {
+            allowed_operations.insert(OperationType::Add, 1);
+        }
+
+        let mut allowed_numerics = Weights::from([(NumberType::Whole, 1)]);
+        if self.negatives {
+            allowed_numerics.insert(NumberType::Negative, 1);
+        }
+
+        OperationConfig {
+            answer_min: 1,
+            answer_max: self.answer_max,
+            value_min: if self.negatives {
+                -self.answer_max / 2
+            } else {
+                0
+            },
+            value_max: self.answer_max,
+            allowed_numerics,
+            allowed_operations,
+        }
+    }
+}
+
+/// Which keys can be used to type answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum InputMethod {
+    #[default]
+    Any,
+    // the number row above the letters
+    NumberRow,
+    Numpad,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // read each equation out loud as it is shown
+    pub read_aloud: bool,
+    pub board_overflow: BoardOverflow,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            read_aloud: true,
+            board_overflow: BoardOverflow::Shrink,
+        }
+    }
+}
+
+/// Log filter directives, only read at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LogFilter(pub String);
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self("info,wgpu_core=warn,wgpu_hal=warn,calculoot=trace".to_string())
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("calculoot").join(SETTINGS_FILE))
+    }
+
+    fn profile_path(profiles: &Profiles, id: u32) -> Option<PathBuf> {
+        profiles.dir(id).map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    /// Reads the settings file, falling back to the defaults if it is missing or broken
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    fn load_from(path: &PathBuf) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        match toml::from_str(&text) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                // the global settings are read before the app is built, so there is no logger yet
+                eprintln!("ignoring invalid settings in {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    /// A profile's settings, or these settings with its grade's difficulty if it has none yet
+    fn for_profile(&self, profiles: &Profiles, id: u32) -> Self {
+        let grade = profiles
+            .profiles
+            .iter()
+            .find(|p| p.id == id)
+            .map_or(0, |p| p.grade);
+        let settings = Self::profile_path(profiles, id)
+            .and_then(|path| Self::load_from(&path))
+            .unwrap_or_else(|| Self {
+                difficulty: DifficultySettings::for_grade(grade),
+                ..self.clone()
+            });
+        Self {
+            display: self.display.clone(),
+            log_filter: self.log_filter.clone(),
+            ..settings
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("no config directory")?;
+        self.save_to(&path)
+    }
+
+    fn save_to(&self, path: &PathBuf) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+fn on_profile_selected(profiles: Res<Profiles>, mut settings: ResMut<Settings>) {
+    let profile = settings.for_profile(&profiles, profiles.selected);
+    if *settings != profile {
+        *settings = profile;
+    }
+}
+
+/// Moves a profile's difficulty to suit their new grade
+fn on_grade_changed(
+    mut changes: EventReader<GradeChanged>,
+    profiles: Res<Profiles>,
+    mut settings: ResMut<Settings>,
+) {
+    for change in changes.read() {
+        let difficulty = DifficultySettings::for_grade(change.grade);
+        if change.id == profiles.selected {
+            settings.difficulty = difficulty;
+            continue;
+        }
+
+        let mut other = settings.for_profile(&profiles, change.id);
+        other.difficulty = difficulty;
+        if let Some(path) = Settings::profile_path(&profiles, change.id)
+            && let Err(e) = other.save_to(&path)
+        {
+            warn!("could not save settings: {e}");
+        }
+    }
+}
+
+/// Keeps volume changes made outside the settings screen, like muting
+fn from_audio(volumes: Res<AudioVolumes>, mut settings: ResMut<Settings>) {
+    if settings.audio != *volumes {
+        settings.audio = volumes.clone();
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut volumes: ResMut<AudioVolumes>,
+    mut read_aloud: ResMut<ReadAloud>,
+) {
+    if let Ok(mut window) = windows.single_mut() {
+        let mode = settings.display.window_mode();
+        if window.mode != mode {
+            window.mode = mode;
+        }
+        let resolution = settings.display.resolution.as_vec2();
+        if settings.display.mode == DisplayMode::Windowed && window.resolution.size() != resolution
+        {
+            window.resolution.set(resolution.x, resolution.y);
+        }
+    }
+    if *volumes != settings.audio {
+        *volumes = settings.audio.clone();
+    }
+    read_aloud.auto = settings.accessibility.read_aloud;
+}
+
+fn save_settings(settings: Res<Settings>, profiles: Res<Profiles>) {
+    // the rest belongs to the profile, so the shared file keeps what it had
+    let shared = Settings {
+        display: settings.display.clone(),
+        log_filter: settings.log_filter.clone(),
+        ..Settings::load()
+    };
+    let result =
+        shared.save().and_then(
+            |_| match Settings::profile_path(&profiles, profiles.selected) {
+                Some(path) => settings.save_to(&path),
+                None => Ok(()),
+            },
+        );
+    match result {
+        Ok(()) => debug!("saved settings"),
+        Err(e) => warn!("could not save settings: {e}"),
+    }
+}
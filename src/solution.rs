@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::banners::Board;
+use crate::level::{ActiveEquation, AnswerSubmitted, AskedAt, EquationLevel};
+use crate::mode::GameMode;
+use crate::settings::Settings;
+
+pub struct SolutionPlugin;
+
+impl Plugin for SolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                explain_wrong_answers.run_if(on_event::<AnswerSubmitted>),
+                show_solution_steps,
+            )
+                .chain()
+                .run_if(in_state(GameMode::InGame)),
+        );
+    }
+}
+
+// how long each step stays on the board, the last one a little longer
+const STEP_SECS: f32 = 2.0;
+const LAST_STEP_SECS: f32 = 3.0;
+
+/// Walks the board through how to solve the equation that was just answered wrong, then puts
+/// the question back. Answers can't be typed until it's done.
+#[derive(Component, Debug)]
+pub struct SolutionSteps {
+    question: String,
+    // one line per step, each showing what's left once an operation is worked out
+    lines: Vec<String>,
+    shown: usize,
+    timer: Timer,
+}
+
+fn explain_wrong_answers(
+    mut submitted: EventReader<AnswerSubmitted>,
+    level: Res<EquationLevel>,
+    settings: Res<Settings>,
+    boards: Query<(Entity, &Board), Without<SolutionSteps>>,
+    mut commands: Commands,
+) {
+    for answer in submitted.read() {
+        // out of lives ends the level instead
+        if answer.correct || level.lives == 0 {
+            continue;
+        }
+        let (Some(equation), Ok((entity, board))) = (level.equation(answer.index), boards.single())
+        else {
+            continue;
+        };
+        let parentheses = settings.difficulty.parentheses();
+        let lines = equation
+            .steps(parentheses)
+            .iter()
+            .map(|step| format!("= {}", step.remaining.written(parentheses)))
+            .collect();
+        commands.entity(entity).insert(SolutionSteps {
+            question: board.text.clone(),
+            lines,
+            shown: 0,
+            // the board flashes the wrong answer before the first step
+            timer: Timer::from_seconds(STEP_SECS / 2.0, TimerMode::Once),
+        });
+    }
+}
+
+/// Marks an active equation whose solution has been shown, so answering it afterwards says
+/// nothing about whether it's known
+#[derive(Component, Debug)]
+pub struct SolutionShown;
+
+fn show_solution_steps(
+    time: Res<Time>,
+    mut boards: Query<(Entity, &mut Board, &mut SolutionSteps)>,
+    active: Query<Entity, With<ActiveEquation>>,
+    mut commands: Commands,
+) {
+    for (entity, mut board, mut steps) in boards.iter_mut() {
+        if !steps.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        if steps.shown == steps.lines.len() {
+            board.text = steps.question.clone();
+            board.answer_hidden = false;
+            commands.entity(entity).remove::<SolutionSteps>();
+            // the retry is timed from when the question is back
+            if let Ok(active) = active.single() {
+                commands
+                    .entity(active)
+                    .insert((AskedAt(time.elapsed_secs()), SolutionShown));
+            }
+            continue;
+        }
+
+        steps.shown += 1;
+        let shown = &steps.lines[..steps.shown];
+        board.text = format!("{}\n{}", steps.question, shown.join("\n"));
+        board.answer.clear();
+        board.answer_hidden = true;
+        let secs = if steps.shown == steps.lines.len() {
+            LAST_STEP_SECS
+        } else {
+            STEP_SECS
+        };
+        steps.timer = Timer::from_seconds(secs, TimerMode::Once);
+    }
+}
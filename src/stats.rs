@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use eq_gen::{Equation, OperationType, Versioned};
+use serde::{Deserialize, Serialize};
+
+use crate::level::{AnswerSubmitted, EquationLevel};
+use crate::menu::{Menu, MenuAction, MenuInput};
+use crate::mode::GameMode;
+use crate::profile::{ProfileSelected, Profiles};
+use crate::review::ReviewDeck;
+use crate::settings::Settings;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttemptLog>();
+        app.add_systems(PreUpdate, load_attempts.run_if(on_event::<ProfileSelected>));
+        app.add_systems(
+            Update,
+            record_attempts.run_if(in_state(GameMode::InGame).and(on_event::<AnswerSubmitted>)),
+        );
+        app.add_systems(OnEnter(GameMode::Stats), spawn_stats_menu);
+        app.add_systems(Update, on_stats_menu.run_if(in_state(GameMode::Stats)));
+    }
+}
+
+const ATTEMPTS_FILE: &str = "stats.jsonl";
+// how many of the most missed facts the stats screen lists
+const MOST_MISSED: usize = 5;
+
+/// One answer the player gave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attempt {
+    // in canonical form, so `4 + 3` and `3 + 4` are recorded as the same fact
+    pub equation: Equation,
+    pub operations: Vec<OperationType>,
+    pub difficulty: u16,
+    pub given: i16,
+    pub expected: i16,
+    pub response_secs: f32,
+    // seconds since the unix epoch
+    pub timestamp: u64,
+    // a wrong answer matching what working left to right instead of by precedence gives
+    #[serde(default)]
+    pub left_to_right: bool,
+}
+
+impl Attempt {
+    pub fn correct(&self) -> bool {
+        self.given == self.expected
+    }
+}
+
+/// Every attempt the active profile has made, oldest first. Kept on disk as one JSON object per
+/// line so each answer only appends to the file.
+#[derive(Resource, Debug, Default)]
+pub struct AttemptLog {
+    pub attempts: Vec<Attempt>,
+}
+
+/// Seconds since the unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn attempts_path(profiles: &Profiles) -> Option<PathBuf> {
+    profiles.active_dir().map(|dir| dir.join(ATTEMPTS_FILE))
+}
+
+fn load_attempts(profiles: Res<Profiles>, mut log: ResMut<AttemptLog>) {
+    log.attempts.clear();
+    let Some(text) = attempts_path(&profiles).and_then(|path| fs::read_to_string(path).ok()) else {
+        return;
+    };
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        // a line cut short by a crash shouldn't lose the rest
+        match Versioned::<Attempt>::from_json(line) {
+            Ok(attempt) => log.attempts.push(attempt),
+            Err(e) => warn!("skipping invalid attempt: {e}"),
+        }
+    }
+    debug!("loaded {} attempts", log.attempts.len());
+}
+
+fn append_attempt(path: &PathBuf, attempt: &Attempt) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let line = Versioned::new(attempt.clone()).to_json()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{line}").map_err(|e| e.to_string())
+}
+
+fn record_attempts(
+    mut submitted: EventReader<AnswerSubmitted>,
+    level: Res<EquationLevel>,
+    profiles: Res<Profiles>,
+    settings: Res<Settings>,
+    mut log: ResMut<AttemptLog>,
+) {
+    let path = attempts_path(&profiles);
+    let timestamp = unix_now();
+    // an answer copied from the solution says nothing about what's known
+    for answer in submitted.read().filter(|answer| !answer.solution_shown) {
+        let Some(equation) = level.equation(answer.index) else {
+            continue;
+        };
+        let attempt = Attempt {
+            equation: equation.canonical(),
+            operations: equation.operations(),
+            difficulty: answer.difficulty,
+            given: answer.given,
+            expected: answer.expected,
+            response_secs: answer.response_secs,
+            timestamp,
+            // only an equation written without its redundant parentheses can be misread
+            left_to_right: settings.difficulty.order_of_operations
+                && !answer.correct
+                && equation.left_to_right() == Some(answer.given),
+        };
+        if let Some(path) = &path
+            && let Err(e) = append_attempt(path, &attempt)
+        {
+            warn!("could not record attempt: {e}");
+        }
+        log.attempts.push(attempt);
+    }
+}
+
+fn operation_name(op: OperationType) -> &'static str {
+    match op {
+        OperationType::Add => "Addition",
+        OperationType::Subtract => "Subtraction",
+        OperationType::Multiply => "Multiplication",
+        OperationType::Divide => "Division",
+    }
+}
+
+impl AttemptLog {
+    /// Correct and total attempts at equations using each operation
+    pub fn accuracy(&self) -> Vec<(OperationType, usize, usize)> {
+        [
+            OperationType::Add,
+            OperationType::Subtract,
+            OperationType::Multiply,
+            OperationType::Divide,
+        ]
+        .into_iter()
+        .map(|op| {
+            let attempts = self
+                .attempts
+                .iter()
+                .filter(|attempt| attempt.operations.contains(&op));
+            let (correct, total) = attempts.fold((0, 0), |(correct, total), attempt| {
+                (correct + attempt.correct() as usize, total + 1)
+            });
+            (op, correct, total)
+        })
+        .collect()
+    }
+
+    /// The equations answered wrong the most, with their answer and how often they were missed
+    pub fn most_missed(&self, count: usize) -> Vec<(String, i16, usize)> {
+        let mut misses: HashMap<(String, i16), usize> = HashMap::new();
+        for attempt in self.attempts.iter().filter(|attempt| !attempt.correct()) {
+            let equation = attempt.equation.to_string();
+            *misses.entry((equation, attempt.expected)).or_default() += 1;
+        }
+        let mut misses: Vec<_> = misses.into_iter().collect();
+        // most missed first, ties in a stable order
+        misses.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        misses
+            .into_iter()
+            .take(count)
+            .map(|((equation, answer), missed)| (equation, answer, missed))
+            .collect()
+    }
+
+    /// How many wrong answers came from working left to right instead of by precedence
+    pub fn left_to_right_misses(&self) -> usize {
+        self.attempts
+            .iter()
+            .filter(|attempt| attempt.left_to_right)
+            .count()
+    }
+
+    fn summary(&self, name: &str) -> String {
+        let mut text = format!("{name}'s stats\n");
+        if self.attempts.is_empty() {
+            text.push_str("\nNo answers yet, go find some loot!");
+            return text;
+        }
+
+        for (op, correct, total) in self.accuracy() {
+            if total == 0 {
+                continue;
+            }
+            let percent = correct * 100 / total;
+            text.push_str(&format!(
+                "\n{}: {correct}/{total} ({percent}%)",
+                operation_name(op)
+            ));
+        }
+
+        let left_to_right = self.left_to_right_misses();
+        if left_to_right > 0 {
+            text.push_str(&format!(
+                "\n\nWorked left to right instead of by precedence: x{left_to_right}"
+            ));
+        }
+
+        let missed = self.most_missed(MOST_MISSED);
+        if !missed.is_empty() {
+            text.push_str("\n\nMost missed");
+            for (equation, answer, count) in missed {
+                text.push_str(&format!("\n{equation} = {answer}  x{count}"));
+            }
+        }
+        text
+    }
+}
+
+fn spawn_stats_menu(
+    log: Res<AttemptLog>,
+    deck: Res<ReviewDeck>,
+    profiles: Res<Profiles>,
+    mut commands: Commands,
+) {
+    let mut text = log.summary(&profiles.active().name);
+    if !deck.cards.is_empty() {
+        text.push_str(&format!(
+            "\n\nReview: {} facts, {} mastered, {} due",
+            deck.cards.len(),
+            deck.mastered(),
+            deck.due_count(unix_now())
+        ));
+    }
+    commands.spawn((
+        Menu::new(text, vec!["Back".to_string()]),
+        StateScoped(GameMode::Stats),
+    ));
+}
+
+fn on_stats_menu(
+    mut actions: EventReader<MenuAction>,
+    menus: Query<(), With<Menu>>,
+    mut next_state: ResMut<NextState<GameMode>>,
+) {
+    for action in actions.read() {
+        // the only item and escape both go back
+        if menus.contains(action.menu)
+            && matches!(action.input, MenuInput::Select | MenuInput::Back)
+        {
+            next_state.set(GameMode::StartMenu);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eq_gen::Value;
+
+    use super::*;
+
+    fn attempt(left: i16, op: OperationType, right: i16, answer: i16, given: i16) -> Attempt {
+        let equation = Equation::new(Value::Number(left), op, Value::Number(right), answer);
+        Attempt {
+            operations: vec![op],
+            difficulty: equation.difficulty(),
+            equation: equation.canonical(),
+            given,
+            expected: answer,
+            response_secs: 3.0,
+            timestamp: 0,
+            left_to_right: false,
+        }
+    }
+
+    #[test]
+    fn most_missed_counts_each_fact_once_whichever_way_round() {
+        let log = AttemptLog {
+            attempts: vec![
+                attempt(4, OperationType::Add, 3, 7, 8),
+                attempt(3, OperationType::Add, 4, 7, 6),
+                attempt(3, OperationType::Add, 4, 7, 7),
+                attempt(9, OperationType::Subtract, 2, 7, 8),
+                attempt(6, OperationType::Multiply, 2, 12, 14),
+                attempt(2, OperationType::Multiply, 6, 12, 10),
+            ],
+        };
+        assert_eq!(
+            log.most_missed(5),
+            [
+                ("2 × 6".to_string(), 12, 2),
+                ("3 + 4".to_string(), 7, 2),
+                ("9 - 2".to_string(), 7, 1),
+            ]
+        );
+        assert_eq!(log.most_missed(1), [("2 × 6".to_string(), 12, 2)]);
+    }
+
+    #[test]
+    fn attempts_read_back_at_the_current_schema() {
+        let attempt = attempt(4, OperationType::Add, 3, 7, 8);
+        let versioned = Versioned::new(attempt.clone()).to_json().unwrap();
+        let read = Versioned::<Attempt>::from_json(&versioned).unwrap();
+        assert_eq!(read.equation, attempt.equation);
+        assert_eq!(read.given, 8);
+
+        let unversioned = serde_json::to_string(&attempt).unwrap();
+        assert!(Versioned::<Attempt>::from_json(&unversioned).is_err());
+    }
+
+    #[test]
+    fn most_missed_leaves_out_correct_answers() {
+        let log = AttemptLog {
+            attempts: vec![
+                attempt(4, OperationType::Add, 3, 7, 7),
+                attempt(8, OperationType::Divide, 2, 4, 4),
+            ],
+        };
+        assert!(log.most_missed(5).is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::background::BackgroundExclusion;
+use crate::banners::Board;
+use crate::camera::VirtualCanvas;
+use crate::layout::{Anchored, ScreenAnchor};
+use crate::level::{AnswerSubmitted, EquationLevel};
+use crate::mode::GameMode;
+use crate::panel::{NineSlice, Panel, PanelContent, PanelSize};
+use crate::profile::Profiles;
+use crate::tween::{Tween, TweenCompleted, Tweens};
+
+pub struct TreasurePlugin;
+
+impl Plugin for TreasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Treasure>();
+        app.init_resource::<LootInFlight>();
+        app.add_systems(OnEnter(GameMode::InGame), spawn_chest_meter);
+        app.add_systems(OnExit(GameMode::InGame), drop_loot_in_flight);
+        app.add_systems(
+            Update,
+            (
+                award_loot,
+                collect_loot,
+                update_chest_meter.run_if(
+                    resource_changed::<Treasure>
+                        .or(resource_changed::<LootInFlight>)
+                        .or(resource_changed::<EquationLevel>),
+                ),
+            )
+                .chain(),
+        );
+    }
+}
+
+const LOOT_ARRIVED: &str = "loot_arrived";
+const MAX_LOOT: u32 = 10;
+
+/// Everything the player has collected
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Treasure {
+    pub gold: u32,
+}
+
+/// Gold already added to the treasure, so it's saved straight away, but still flying to the chest
+/// meter and not shown on it until it lands
+#[derive(Resource, Debug, Default)]
+struct LootInFlight(u32);
+
+/// Shows how much treasure is in the chest
+#[derive(Component)]
+struct ChestMeter;
+
+/// The player's name, in their avatar colour
+#[derive(Component)]
+struct ChestMeterText;
+
+/// The gold and lives after the name
+#[derive(Component)]
+struct ChestMeterStats;
+
+/// Gold on its way from the board to the chest meter
+#[derive(Component)]
+struct Loot(u32);
+
+fn spawn_chest_meter(
+    asset_server: Res<AssetServer>,
+    canvas: Res<VirtualCanvas>,
+    treasure: Res<Treasure>,
+    level: Res<EquationLevel>,
+    profiles: Res<Profiles>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut commands: Commands,
+) {
+    let slice = NineSlice::from_grid(
+        asset_server.load("board.png"),
+        UVec2::new(31, 33),
+        &mut texture_atlas_layouts,
+    );
+    let panel = Panel::new(
+        slice,
+        PanelSize::Content {
+            padding: Vec2::new(16.0, 12.0),
+            min_tiles: UVec2::new(3, 2),
+        },
+    );
+    let anchored = Anchored::new(
+        ScreenAnchor::BottomLeft,
+        Vec2::splat(16.0),
+        panel.tiles(None).as_vec2() * panel.slice.tile_size,
+    );
+    let bounds = anchored.rect(canvas.size);
+    let font = TextFont {
+        font: asset_server.load("monogram-extended.ttf"),
+        font_size: 32.0,
+        font_smoothing: bevy::text::FontSmoothing::None,
+        ..Default::default()
+    };
+    let profile = profiles.active();
+
+    let stats = commands
+        .spawn((
+            ChestMeterStats,
+            TextSpan::new(meter_text(treasure.gold, &level)),
+            font.clone(),
+            TextColor(Color::srgb(51.0 / 255.0, 50.0 / 255.0, 61.0 / 255.0)),
+        ))
+        .id();
+    let text = commands
+        .spawn((
+            ChestMeterText,
+            PanelContent,
+            Text2d::new(profile.name.clone()),
+            font,
+            TextColor(profile.color.color()),
+            Anchor::CenterLeft,
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ))
+        .add_child(stats)
+        .id();
+
+    commands
+        .spawn((
+            ChestMeter,
+            Transform::from_translation(bounds.min.extend(5.0)),
+            BackgroundExclusion(bounds),
+            anchored,
+            panel,
+            StateScoped(GameMode::InGame),
+        ))
+        .add_child(text);
+}
+
+fn meter_text(gold: u32, level: &EquationLevel) -> String {
+    format!("  Gold {gold}  Lives {}", level.lives)
+}
+
+/// Where loot flies from and to
+#[derive(SystemParam)]
+struct LootPath<'w, 's> {
+    canvas: Res<'w, VirtualCanvas>,
+    boards: Query<'w, 's, &'static Anchored, With<Board>>,
+    meters: Query<'w, 's, &'static Anchored, With<ChestMeter>>,
+}
+
+/// Adds gold for every correct answer and sends it flying from the board to the chest meter
+fn award_loot(
+    mut submitted: EventReader<AnswerSubmitted>,
+    mut treasure: ResMut<Treasure>,
+    mut in_flight: ResMut<LootInFlight>,
+    LootPath {
+        canvas,
+        boards,
+        meters,
+    }: LootPath,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for e in submitted.read().filter(|e| e.correct) {
+        let amount = (1 + e.difficulty as u32 / 4).min(MAX_LOOT);
+        treasure.gold += amount;
+        let (Ok(board), Ok(meter)) = (boards.single(), meters.single()) else {
+            continue;
+        };
+        in_flight.0 += amount;
+        let start = board.rect(canvas.size).center().extend(10.0);
+        let lifted = start + Vec3::Y * 16.0;
+        let end = meter.rect(canvas.size).center().extend(10.0);
+
+        commands.spawn((
+            Loot(amount),
+            StateScoped(GameMode::InGame),
+            Text2d::new(format!("+{amount}")),
+            TextFont {
+                font: asset_server.load("monogram-extended.ttf"),
+                font_size: 32.0,
+                font_smoothing: bevy::text::FontSmoothing::None,
+                ..Default::default()
+            },
+            TextColor(Color::srgb(1.0, 0.84, 0.2)),
+            Transform::from_translation(start),
+            Tweens::new()
+                .then_parallel([
+                    Tween::translation(start, lifted, 0.3, EaseFunction::CubicOut),
+                    Tween::alpha(0.0, 1.0, 0.3, EaseFunction::Linear),
+                ])
+                .then(
+                    Tween::translation(lifted, end, 0.6, EaseFunction::CubicInOut)
+                        .with_label(LOOT_ARRIVED),
+                )
+                .then(Tween::alpha(1.0, 0.0, 0.2, EaseFunction::Linear))
+                .despawn_when_done(),
+        ));
+    }
+}
+
+fn collect_loot(
+    mut completed: EventReader<TweenCompleted>,
+    loot: Query<&Loot>,
+    meter_texts: Query<Entity, With<ChestMeterText>>,
+    mut in_flight: ResMut<LootInFlight>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut commands: Commands,
+) {
+    for e in completed.read().filter(|e| e.label == LOOT_ARRIVED) {
+        let Ok(loot) = loot.get(e.entity) else {
+            continue;
+        };
+        in_flight.0 = in_flight.0.saturating_sub(loot.0);
+        sfx.write(PlaySfx(Sfx::Coin));
+
+        // pulse the meter as the gold lands
+        for entity in meter_texts.iter() {
+            commands.entity(entity).insert(
+                Tweens::new()
+                    .then(Tween::scale(
+                        Vec3::ONE,
+                        Vec3::splat(1.25),
+                        0.1,
+                        EaseFunction::QuadraticOut,
+                    ))
+                    .then(Tween::scale(
+                        Vec3::splat(1.25),
+                        Vec3::ONE,
+                        0.2,
+                        EaseFunction::QuadraticIn,
+                    )),
+            );
+        }
+    }
+}
+
+fn update_chest_meter(
+    treasure: Res<Treasure>,
+    in_flight: Res<LootInFlight>,
+    level: Res<EquationLevel>,
+    mut spans: Query<&mut TextSpan, With<ChestMeterStats>>,
+) {
+    for mut span in spans.iter_mut() {
+        span.0 = meter_text(treasure.gold.saturating_sub(in_flight.0), &level);
+    }
+}
+
+/// Loot still flying when the level is left goes with it, but is already in the treasure
+fn drop_loot_in_flight(mut in_flight: ResMut<LootInFlight>) {
+    in_flight.0 = 0;
+}
@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>();
+        app.add_systems(Update, run_tweens);
+    }
+}
+
+/// The property a tween animates
+#[derive(Debug, Clone, Copy)]
+#[allow(unused)]
+pub enum TweenTarget {
+    Translation { from: Vec3, to: Vec3 },
+    Scale { from: Vec3, to: Vec3 },
+    // the sprite's color, or the text color for text
+    Color { from: Color, to: Color },
+    // only the alpha of the sprite or text color
+    Alpha { from: f32, to: f32 },
+}
+
+/// Animates a single property over time
+#[derive(Debug, Clone)]
+pub struct Tween {
+    pub target: TweenTarget,
+    pub duration: f32,
+    pub ease: EaseFunction,
+    // sent in a `TweenCompleted` when this tween finishes
+    pub label: Option<&'static str>,
+    elapsed: f32,
+}
+
+impl Tween {
+    pub fn new(target: TweenTarget, duration: f32, ease: EaseFunction) -> Self {
+        Self {
+            target,
+            duration,
+            ease,
+            label: None,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn translation(from: Vec3, to: Vec3, duration: f32, ease: EaseFunction) -> Self {
+        Self::new(TweenTarget::Translation { from, to }, duration, ease)
+    }
+
+    pub fn scale(from: Vec3, to: Vec3, duration: f32, ease: EaseFunction) -> Self {
+        Self::new(TweenTarget::Scale { from, to }, duration, ease)
+    }
+
+    pub fn color(from: Color, to: Color, duration: f32, ease: EaseFunction) -> Self {
+        Self::new(TweenTarget::Color { from, to }, duration, ease)
+    }
+
+    pub fn alpha(from: f32, to: f32, duration: f32, ease: EaseFunction) -> Self {
+        Self::new(TweenTarget::Alpha { from, to }, duration, ease)
+    }
+
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.ease
+                .sample_clamped((self.elapsed / self.duration).min(1.0))
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TweenStep {
+    // every tween in the group runs at once, the step ends when the longest one finishes
+    Parallel(Vec<Tween>),
+    Delay(f32),
+}
+
+/// A sequence of tweens run one step after another. Removing the component cancels whatever is
+/// left, leaving the entity as it was on the last frame.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Tweens {
+    steps: VecDeque<TweenStep>,
+    // time spent on the current step
+    elapsed: f32,
+    // despawn the entity once the sequence finishes
+    despawn: bool,
+}
+
+#[allow(unused)]
+impl Tweens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `tween` after everything added so far
+    pub fn then(mut self, tween: Tween) -> Self {
+        self.steps.push_back(TweenStep::Parallel(vec![tween]));
+        self
+    }
+
+    /// Runs all of `tweens` together after everything added so far
+    pub fn then_parallel(mut self, tweens: impl IntoIterator<Item = Tween>) -> Self {
+        self.steps
+            .push_back(TweenStep::Parallel(tweens.into_iter().collect()));
+        self
+    }
+
+    /// Waits `secs` before moving on to the next step
+    pub fn delay(mut self, secs: f32) -> Self {
+        self.steps.push_back(TweenStep::Delay(secs));
+        self
+    }
+
+    pub fn despawn_when_done(mut self) -> Self {
+        self.despawn = true;
+        self
+    }
+
+    // moves the sequence on by `delta` seconds, calling `apply` with each running tween's target
+    // and how far through its easing it is, and returns the labels of the tweens that finished.
+    // Time left over when a step finishes goes on to the next one so chained steps don't drift.
+    fn advance(
+        &mut self,
+        delta: f32,
+        mut apply: impl FnMut(TweenTarget, f32),
+    ) -> Vec<&'static str> {
+        let mut finished = Vec::new();
+        self.elapsed += delta;
+        while let Some(step) = self.steps.front_mut() {
+            let elapsed = self.elapsed;
+            let duration = match step {
+                TweenStep::Delay(secs) => *secs,
+                TweenStep::Parallel(group) => {
+                    for tween in group.iter_mut() {
+                        if tween.finished() {
+                            continue;
+                        }
+                        tween.elapsed = elapsed;
+                        apply(tween.target, tween.progress());
+                        if tween.finished()
+                            && let Some(label) = tween.label
+                        {
+                            finished.push(label);
+                        }
+                    }
+                    group.iter().map(|tween| tween.duration).fold(0.0, f32::max)
+                }
+            };
+            if elapsed < duration {
+                break;
+            }
+            self.steps.pop_front();
+            self.elapsed -= duration;
+        }
+        finished
+    }
+}
+
+/// Sent for each labelled tween as it finishes
+#[derive(Event, Debug)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub label: &'static str,
+}
+
+type TweenItem<'a> = (
+    Entity,
+    &'a mut Tweens,
+    Option<&'a mut Transform>,
+    Option<&'a mut Sprite>,
+    Option<&'a mut TextColor>,
+);
+
+fn run_tweens(
+    time: Res<Time>,
+    mut tweens: Query<TweenItem>,
+    mut completed: EventWriter<TweenCompleted>,
+    mut commands: Commands,
+) {
+    for (entity, mut sequence, mut transform, mut sprite, mut text_color) in tweens.iter_mut() {
+        let finished = sequence.advance(time.delta_secs(), |target, t| match target {
+            TweenTarget::Translation { from, to } => {
+                if let Some(transform) = transform.as_mut() {
+                    transform.translation = from.lerp(to, t);
+                }
+            }
+            TweenTarget::Scale { from, to } => {
+                if let Some(transform) = transform.as_mut() {
+                    transform.scale = from.lerp(to, t);
+                }
+            }
+            TweenTarget::Color { from, to } => {
+                let color = from.mix(&to, t);
+                if let Some(sprite) = sprite.as_mut() {
+                    sprite.color = color;
+                }
+                if let Some(text_color) = text_color.as_mut() {
+                    text_color.0 = color;
+                }
+            }
+            TweenTarget::Alpha { from, to } => {
+                let alpha = from.lerp(to, t);
+                if let Some(sprite) = sprite.as_mut() {
+                    sprite.color.set_alpha(alpha);
+                }
+                if let Some(text_color) = text_color.as_mut() {
+                    text_color.0.set_alpha(alpha);
+                }
+            }
+        });
+        for label in finished {
+            completed.write(TweenCompleted { entity, label });
+        }
+
+        if sequence.steps.is_empty() {
+            if sequence.despawn {
+                commands.entity(entity).despawn();
+            } else {
+                commands.entity(entity).remove::<Tweens>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(duration: f32) -> Tween {
+        Tween::translation(Vec3::ZERO, Vec3::X, duration, EaseFunction::Linear)
+    }
+
+    // the x of every translation applied while advancing
+    fn advance(tweens: &mut Tweens, delta: f32) -> (Vec<f32>, Vec<&'static str>) {
+        let mut applied = Vec::new();
+        let finished = tweens.advance(delta, |target, t| {
+            if let TweenTarget::Translation { from, to } = target {
+                applied.push(from.lerp(to, t).x);
+            }
+        });
+        (applied, finished)
+    }
+
+    #[test]
+    fn eases_start_and_end_exactly() {
+        for ease in [
+            EaseFunction::Linear,
+            EaseFunction::CubicIn,
+            EaseFunction::CubicOut,
+            EaseFunction::BounceOut,
+        ] {
+            let mut tween = Tween::new(TweenTarget::Alpha { from: 0.0, to: 1.0 }, 2.0, ease);
+            assert_eq!(tween.progress(), 0.0);
+            tween.elapsed = 2.0;
+            assert_eq!(tween.progress(), 1.0);
+            // overshooting the end doesn't go past it
+            tween.elapsed = 3.0;
+            assert_eq!(tween.progress(), 1.0);
+        }
+        let instant = Tween::new(
+            TweenTarget::Alpha { from: 0.0, to: 1.0 },
+            0.0,
+            EaseFunction::Linear,
+        );
+        assert_eq!(instant.progress(), 1.0);
+    }
+
+    #[test]
+    fn steps_run_one_after_another() {
+        let mut tweens = Tweens::new()
+            .then(slide(1.0).with_label("first"))
+            .then(slide(1.0).with_label("second"));
+
+        let (applied, finished) = advance(&mut tweens, 0.5);
+        assert_eq!(applied, [0.5]);
+        assert!(finished.is_empty());
+
+        let (applied, finished) = advance(&mut tweens, 0.5);
+        assert_eq!(applied, [1.0, 0.0]);
+        assert_eq!(finished, ["first"]);
+        assert_eq!(tweens.steps.len(), 1);
+
+        let (_, finished) = advance(&mut tweens, 1.0);
+        assert_eq!(finished, ["second"]);
+        assert!(tweens.steps.is_empty());
+    }
+
+    #[test]
+    fn leftover_time_carries_into_the_next_step() {
+        let mut tweens = Tweens::new().then(slide(1.0)).delay(0.25).then(slide(1.0));
+
+        // the frame that ends the first step also covers the whole delay
+        let (applied, _) = advance(&mut tweens, 1.5);
+        assert_eq!(applied, [1.0, 0.25]);
+        assert_eq!(tweens.steps.len(), 1);
+
+        let (applied, _) = advance(&mut tweens, 0.5);
+        assert_eq!(applied, [0.75]);
+    }
+
+    #[test]
+    fn parallel_steps_last_as_long_as_the_longest_tween() {
+        let mut tweens = Tweens::new().then_parallel([slide(0.5), slide(1.0)]);
+
+        let (applied, _) = advance(&mut tweens, 0.75);
+        assert_eq!(applied, [1.0, 0.75]);
+        assert_eq!(tweens.steps.len(), 1);
+
+        // the finished tween is left where it ended
+        let (applied, _) = advance(&mut tweens, 0.25);
+        assert_eq!(applied, [1.0]);
+        assert!(tweens.steps.is_empty());
+    }
+}
@@ -0,0 +1,21 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eq_gen::Worksheet;
+
+/// Writes the worksheet and its answer key to `dir`, returning both paths
+pub fn export(worksheet: &Worksheet, dir: &Path) -> Result<(PathBuf, PathBuf), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let sheet = dir.join(worksheet.file_name(false));
+    let key = dir.join(worksheet.file_name(true));
+    fs::write(&sheet, worksheet.to_html(false)).map_err(|e| e.to_string())?;
+    fs::write(&key, worksheet.to_html(true)).map_err(|e| e.to_string())?;
+    Ok((sheet, key))
+}
+
+/// Where the game puts worksheets, the documents directory if there is one
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::document_dir()
+        .or_else(dirs::data_dir)
+        .map(|dir| dir.join("calculoot").join("worksheets"))
+}